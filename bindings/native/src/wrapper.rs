@@ -145,7 +145,11 @@ impl StrongholdWrapper {
             vault_path: VAULT_PATH.as_bytes().to_vec(),
         };
 
-        let sign_procedure = WriteVault { data, location };
+        let sign_procedure = WriteVault {
+            data,
+            location,
+            kind: None,
+        };
 
         if let Err(_err) = self.client.execute_procedure(sign_procedure) {
             return Err(WrapperError::ExecuteProcedure(format!("{:?}", _err)));
@@ -14,9 +14,10 @@ pub use primitives::{
     AeadCipher, AeadDecrypt, AeadEncrypt, AesKeyWrapCipher, AesKeyWrapDecrypt, AesKeyWrapEncrypt, BIP39Generate,
     BIP39Recover, Chain, ChainCode, ConcatKdf, ConcatSecret, CopyRecord, Ed25519Sign, GarbageCollect, GenerateKey,
     Hkdf, Hmac, KeyType, MnemonicLanguage, Pbkdf2Hmac, PublicKey, RevokeData, Sha2Hash, Slip10Derive,
-    Slip10DeriveInput, Slip10Generate, StrongholdProcedure, WriteVault, X25519DiffieHellman,
+    Slip10DeriveBatch, Slip10DeriveInput, Slip10Generate, StrongholdProcedure, WriteVault, X25519DiffieHellman,
 };
 pub use types::{
-    DeriveSecret, FatalProcedureError, GenerateSecret, Procedure, ProcedureError, ProcedureOutput, UseSecret,
+    DeriveSecret, DeriveSecrets, FatalProcedureError, GenerateSecret, Procedure, ProcedureError, ProcedureOutput,
+    RecordKind, UseSecret,
 };
-pub(crate) use types::{Products, Runner};
+pub(crate) use types::{MultiProducts, Products, Runner};
@@ -14,9 +14,10 @@ use engine::{
 use crate::{
     derive_vault_id,
     procedures::{
-        FatalProcedureError, Procedure, ProcedureError, ProcedureOutput, Products, Runner, StrongholdProcedure,
+        FatalProcedureError, MultiProducts, Procedure, ProcedureError, ProcedureOutput, Products, RecordKind, Runner,
+        StrongholdProcedure,
     },
-    Client, ClientError, ClientVault, KeyStore, Location, Provider, RecordError, Store, VaultError,
+    Client, ClientError, ClientVault, KeyStore, Limits, Location, Provider, RecordError, Store, VaultError,
 };
 use stronghold_utils::random as rand;
 pub const DEFAULT_RANDOM_HINT_SIZE: usize = 24;
@@ -74,6 +75,7 @@ impl Runner for Client {
         &self,
         source_locations: [Location; N],
         target_location: &Location,
+        target_kind: RecordKind,
         f: F,
     ) -> Result<T, VaultError<FatalProcedureError>>
     where
@@ -88,7 +90,7 @@ impl Runner for Client {
             Ok(secret)
         };
 
-        let random_hint = RecordHint::new(rand::variable_bytestring(DEFAULT_RANDOM_HINT_SIZE)).unwrap();
+        let hint = target_kind.to_hint();
 
         let mut keystore = self.keystore.write().map_err(|_| VaultError::LockPoisoned)?;
         let mut db = self.db.write().map_err(|_| VaultError::LockPoisoned)?;
@@ -106,14 +108,7 @@ impl Runner for Client {
             .get_key(target_vid)
             .ok_or(VaultError::VaultNotFound(target_vid))?;
 
-        let res = db.exec_procedure(
-            sources,
-            &target_key,
-            target_vid,
-            target_rid,
-            random_hint,
-            execute_procedure,
-        );
+        let res = db.exec_procedure(sources, &target_key, target_vid, target_rid, hint, execute_procedure);
 
         match res {
             Ok(()) => Ok(ret.unwrap()),
@@ -121,7 +116,59 @@ impl Runner for Client {
         }
     }
 
-    fn write_to_vault(&self, location: &Location, value: Vec<u8>) -> Result<(), RecordError> {
+    fn exec_proc_multi<F, T, const N: usize>(
+        &self,
+        source_locations: [Location; N],
+        targets: &[(Location, RecordKind)],
+        f: F,
+    ) -> Result<T, VaultError<FatalProcedureError>>
+    where
+        F: FnOnce([Buffer<u8>; N]) -> Result<MultiProducts<T>, FatalProcedureError>,
+    {
+        let mut ret = None;
+        let execute_procedure = |guards: [Buffer<u8>; N]| {
+            let MultiProducts { output: plain, secrets } = f(guards)?;
+            ret = Some(plain);
+            Ok(secrets)
+        };
+
+        let mut keystore = self.keystore.write().map_err(|_| VaultError::LockPoisoned)?;
+        let mut db = self.db.write().map_err(|_| VaultError::LockPoisoned)?;
+
+        let sources: [(Key<Provider>, VaultId, RecordId); N] = resolve_locations!(self, source_locations, keystore)?;
+
+        // keys for new vaults are removed again if the procedure fails, the vaults themselves
+        // are only created by the write
+        let mut created = Vec::new();
+        let resolved = targets
+            .iter()
+            .map(|(location, kind)| {
+                let (vault_id, record_id) = location.resolve();
+                if !keystore.vault_exists(vault_id) {
+                    keystore.create_key(vault_id).map_err(|_| {
+                        VaultError::Procedure("failed to generate key from keystore".to_string().into())
+                    })?;
+                    created.push(vault_id);
+                }
+                let key = keystore.get_key(vault_id).ok_or(VaultError::VaultNotFound(vault_id))?;
+                Ok((key, vault_id, record_id, kind.to_hint()))
+            })
+            .collect::<Result<Vec<_>, _>>();
+
+        let res = resolved.and_then(|resolved| db.exec_procedure_multi(sources, resolved, execute_procedure));
+
+        match res {
+            Ok(()) => Ok(ret.unwrap()),
+            Err(e) => {
+                for vault_id in created {
+                    keystore.take_key(vault_id);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    fn write_record(&self, location: &Location, value: Vec<u8>, kind: RecordKind) -> Result<(), RecordError> {
         let (vault_id, record_id) = location.resolve();
 
         let mut keystore = self.keystore.write().map_err(|_| RecordError::LockPoisoned)?;
@@ -132,9 +179,8 @@ impl Runner for Client {
             let key = keystore.create_key(vault_id).map_err(|_| RecordError::InvalidKey)?;
             db.init_vault(&key, vault_id);
         }
-        let random_hint = RecordHint::new(rand::variable_bytestring(DEFAULT_RANDOM_HINT_SIZE)).unwrap();
         let key = keystore.take_key(vault_id).unwrap();
-        let res = db.write(&key, vault_id, record_id, &value, random_hint);
+        let res = db.write(&key, vault_id, record_id, &value, kind.to_hint());
 
         // this should return an error
         keystore
@@ -175,6 +221,26 @@ impl Runner for Client {
             .expect("Inserting key into vault failed");
         Ok(true)
     }
+
+    fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    fn record_kinds<const N: usize>(
+        &self,
+        locations: &[Location; N],
+    ) -> Result<[RecordKind; N], VaultError<FatalProcedureError>> {
+        let keystore = self.keystore.read().map_err(|_| VaultError::LockPoisoned)?;
+        let db = self.db.read().map_err(|_| VaultError::LockPoisoned)?;
+        let ids: [ResolvedLocation; N] = resolve_locations!(self, locations.clone(), keystore)?;
+
+        let mut kinds = [RecordKind::Raw; N];
+        for (kind, (key, vault_id, record_id)) in kinds.iter_mut().zip(ids) {
+            let hint = db.get_hint(&key, vault_id, record_id)?;
+            *kind = RecordKind::from_hint(&hint);
+        }
+        Ok(kinds)
+    }
 }
 
 impl Client {
@@ -4,7 +4,7 @@
 use std::str::FromStr;
 
 use super::types::*;
-use crate::{derive_record_id, derive_vault_id, Client, ClientError, Location, UseKey};
+use crate::{derive_record_id, derive_vault_id, Client, ClientError, DerivedRecord, Location, UseKey};
 pub use crypto::keys::slip10::{Chain, ChainCode};
 use crypto::{
     ciphers::{
@@ -44,6 +44,7 @@ pub enum StrongholdProcedure {
     CopyRecord(CopyRecord),
     Slip10Generate(Slip10Generate),
     Slip10Derive(Slip10Derive),
+    Slip10DeriveBatch(Slip10DeriveBatch),
     BIP39Generate(BIP39Generate),
     BIP39Recover(BIP39Recover),
     PublicKey(PublicKey),
@@ -76,6 +77,7 @@ impl Procedure for StrongholdProcedure {
             CopyRecord(proc) => proc.execute(runner).map(|o| o.into()),
             Slip10Generate(proc) => proc.execute(runner).map(|o| o.into()),
             Slip10Derive(proc) => proc.execute(runner).map(|o| o.into()),
+            Slip10DeriveBatch(proc) => proc.execute(runner).map(|o| o.into()),
             BIP39Generate(proc) => proc.execute(runner).map(|o| o.into()),
             BIP39Recover(proc) => proc.execute(runner).map(|o| o.into()),
             GenerateKey(proc) => proc.execute(runner).map(|o| o.into()),
@@ -110,6 +112,14 @@ impl StrongholdProcedure {
                 input: Slip10DeriveInput::Key(input),
                 ..
             })
+            | StrongholdProcedure::Slip10DeriveBatch(Slip10DeriveBatch {
+                input: Slip10DeriveInput::Seed(input),
+                ..
+            })
+            | StrongholdProcedure::Slip10DeriveBatch(Slip10DeriveBatch {
+                input: Slip10DeriveInput::Key(input),
+                ..
+            })
             | StrongholdProcedure::PublicKey(PublicKey { private_key: input, .. })
             | StrongholdProcedure::Ed25519Sign(Ed25519Sign { private_key: input, .. })
             | StrongholdProcedure::X25519DiffieHellman(X25519DiffieHellman { private_key: input, .. })
@@ -123,7 +133,62 @@ impl StrongholdProcedure {
             _ => None,
         }
     }
-    pub(crate) fn output(&self) -> Option<Location> {
+    /// Returns the records that the procedure derives via SLIP10.
+    pub(crate) fn derived_records(&self) -> Vec<DerivedRecord> {
+        match self {
+            StrongholdProcedure::Slip10Derive(proc) => vec![DerivedRecord {
+                location: proc.output.clone(),
+                source: proc.source()[0].clone(),
+                chain: proc.chain.clone(),
+            }],
+            StrongholdProcedure::Slip10DeriveBatch(proc) => proc
+                .outputs
+                .iter()
+                .zip(proc.chains.iter())
+                .map(|(location, chain)| DerivedRecord {
+                    location: location.clone(),
+                    source: proc.source()[0].clone(),
+                    chain: chain.clone(),
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns all locations that the procedure writes a secret to.
+    pub(crate) fn outputs(&self) -> Vec<Location> {
+        match self {
+            StrongholdProcedure::Slip10DeriveBatch(Slip10DeriveBatch { outputs, .. }) => outputs.clone(),
+            other => other.output().into_iter().collect(),
+        }
+    }
+
+    /// Returns the [`RecordKind`] of each secret written to [`Self::outputs`], given the kinds of
+    /// the records the procedure reads from, in the order of [`Self::inputs`].
+    pub(crate) fn output_kinds(&self, input_kinds: &[RecordKind]) -> Vec<RecordKind> {
+        fn sources<const N: usize>(input_kinds: &[RecordKind]) -> [RecordKind; N] {
+            input_kinds.try_into().unwrap_or([RecordKind::Raw; N])
+        }
+        match self {
+            StrongholdProcedure::WriteVault(proc) => vec![proc.target_kind()],
+            StrongholdProcedure::Slip10Generate(proc) => vec![proc.target_kind()],
+            StrongholdProcedure::BIP39Generate(proc) => vec![proc.target_kind()],
+            StrongholdProcedure::BIP39Recover(proc) => vec![proc.target_kind()],
+            StrongholdProcedure::GenerateKey(proc) => vec![proc.target_kind()],
+            StrongholdProcedure::Pbkdf2Hmac(proc) => vec![proc.target_kind()],
+            StrongholdProcedure::CopyRecord(proc) => vec![proc.target_kind(sources(input_kinds))],
+            StrongholdProcedure::Slip10Derive(proc) => vec![proc.target_kind(sources(input_kinds))],
+            StrongholdProcedure::X25519DiffieHellman(proc) => vec![proc.target_kind(sources(input_kinds))],
+            StrongholdProcedure::Hkdf(proc) => vec![proc.target_kind(sources(input_kinds))],
+            StrongholdProcedure::ConcatKdf(proc) => vec![proc.target_kind(sources(input_kinds))],
+            StrongholdProcedure::AesKeyWrapDecrypt(proc) => vec![proc.target_kind(sources(input_kinds))],
+            StrongholdProcedure::ConcatSecret(proc) => vec![proc.target_kind(sources(input_kinds))],
+            StrongholdProcedure::Slip10DeriveBatch(proc) => proc.target_kinds(sources(input_kinds)),
+            _ => Vec::new(),
+        }
+    }
+
+    fn output(&self) -> Option<Location> {
         match self {
             StrongholdProcedure::WriteVault(WriteVault { location: output, .. })
             | StrongholdProcedure::CopyRecord(CopyRecord { target: output, .. })
@@ -135,10 +200,139 @@ impl StrongholdProcedure {
             | StrongholdProcedure::X25519DiffieHellman(X25519DiffieHellman { shared_key: output, .. })
             | StrongholdProcedure::Hkdf(Hkdf { okm: output, .. })
             | StrongholdProcedure::ConcatKdf(ConcatKdf { output, .. })
-            | StrongholdProcedure::Pbkdf2Hmac(Pbkdf2Hmac { output, .. }) => Some(output.clone()),
+            | StrongholdProcedure::Pbkdf2Hmac(Pbkdf2Hmac { output, .. })
+            | StrongholdProcedure::AesKeyWrapDecrypt(AesKeyWrapDecrypt { output, .. })
+            | StrongholdProcedure::ConcatSecret(ConcatSecret {
+                output_location: output,
+                ..
+            }) => Some(output.clone()),
             _ => None,
         }
     }
+
+    /// Returns all locations whose secret is read by the procedure, together with the length
+    /// that the secret is required to have and the [`RecordKind`] it is required to be of.
+    pub(crate) fn inputs(&self) -> Vec<(Location, InputLength, Option<RecordKind>)> {
+        self.input_lengths()
+            .into_iter()
+            .zip(self.input_kinds())
+            .map(|((location, length), kind)| (location, length, kind))
+            .collect()
+    }
+
+    fn input_kinds(&self) -> Vec<Option<RecordKind>> {
+        match self {
+            StrongholdProcedure::CopyRecord(proc) => proc.source_kinds().to_vec(),
+            StrongholdProcedure::Slip10Derive(proc) => proc.source_kinds().to_vec(),
+            StrongholdProcedure::Slip10DeriveBatch(proc) => proc.source_kinds().to_vec(),
+            StrongholdProcedure::PublicKey(proc) => proc.source_kinds().to_vec(),
+            StrongholdProcedure::Ed25519Sign(proc) => proc.source_kinds().to_vec(),
+            StrongholdProcedure::X25519DiffieHellman(proc) => proc.source_kinds().to_vec(),
+            StrongholdProcedure::Hmac(proc) => proc.source_kinds().to_vec(),
+            StrongholdProcedure::Hkdf(proc) => proc.source_kinds().to_vec(),
+            StrongholdProcedure::ConcatKdf(proc) => proc.source_kinds().to_vec(),
+            StrongholdProcedure::AeadEncrypt(proc) => proc.source_kinds().to_vec(),
+            StrongholdProcedure::AeadDecrypt(proc) => proc.source_kinds().to_vec(),
+            StrongholdProcedure::AesKeyWrapEncrypt(proc) => proc.source_kinds().to_vec(),
+            StrongholdProcedure::AesKeyWrapDecrypt(proc) => proc.source_kinds().to_vec(),
+            StrongholdProcedure::ConcatSecret(proc) => proc.source_kinds().to_vec(),
+            #[cfg(feature = "insecure")]
+            StrongholdProcedure::CompareSecret(proc) => proc.source_kinds().to_vec(),
+            StrongholdProcedure::WriteVault(_)
+            | StrongholdProcedure::RevokeData(_)
+            | StrongholdProcedure::GarbageCollect(_)
+            | StrongholdProcedure::Slip10Generate(_)
+            | StrongholdProcedure::BIP39Generate(_)
+            | StrongholdProcedure::BIP39Recover(_)
+            | StrongholdProcedure::GenerateKey(_)
+            | StrongholdProcedure::Pbkdf2Hmac(_) => Vec::new(),
+        }
+    }
+
+    fn input_lengths(&self) -> Vec<(Location, InputLength)> {
+        use InputLength::*;
+        match self {
+            StrongholdProcedure::CopyRecord(CopyRecord { source, .. }) => vec![(source.clone(), Any)],
+            StrongholdProcedure::Slip10Derive(Slip10Derive { input, .. })
+            | StrongholdProcedure::Slip10DeriveBatch(Slip10DeriveBatch { input, .. }) => match input {
+                Slip10DeriveInput::Seed(location) => vec![(location.clone(), Any)],
+                // chain code and private key
+                Slip10DeriveInput::Key(location) => vec![(location.clone(), Exact(64))],
+            },
+            StrongholdProcedure::PublicKey(PublicKey { ty, private_key }) => {
+                vec![(private_key.clone(), InputLength::for_key_type(ty))]
+            }
+            StrongholdProcedure::Ed25519Sign(Ed25519Sign { private_key, .. }) => {
+                vec![(private_key.clone(), AtLeast(ed25519::SECRET_KEY_LENGTH))]
+            }
+            StrongholdProcedure::X25519DiffieHellman(X25519DiffieHellman { private_key, .. }) => {
+                vec![(private_key.clone(), Exact(x25519::SECRET_KEY_LENGTH))]
+            }
+            StrongholdProcedure::Hmac(Hmac { key, .. }) => vec![(key.clone(), Any)],
+            StrongholdProcedure::Hkdf(Hkdf { ikm, .. }) => vec![(ikm.clone(), Any)],
+            StrongholdProcedure::ConcatKdf(ConcatKdf { shared_secret, .. }) => vec![(shared_secret.clone(), Any)],
+            StrongholdProcedure::AeadEncrypt(AeadEncrypt { cipher, key, .. })
+            | StrongholdProcedure::AeadDecrypt(AeadDecrypt { cipher, key, .. }) => {
+                let length = match cipher {
+                    AeadCipher::Aes256Gcm => Aes256Gcm::KEY_LENGTH,
+                    AeadCipher::XChaCha20Poly1305 => XChaCha20Poly1305::KEY_LENGTH,
+                };
+                vec![(key.clone(), Exact(length))]
+            }
+            StrongholdProcedure::AesKeyWrapEncrypt(AesKeyWrapEncrypt {
+                encryption_key,
+                wrap_key,
+                ..
+            }) => vec![
+                (encryption_key.clone(), Exact(Aes256Gcm::KEY_LENGTH)),
+                (wrap_key.clone(), Any),
+            ],
+            StrongholdProcedure::AesKeyWrapDecrypt(AesKeyWrapDecrypt { decryption_key, .. }) => {
+                vec![(decryption_key.clone(), Exact(Aes256Gcm::KEY_LENGTH))]
+            }
+            StrongholdProcedure::ConcatSecret(ConcatSecret {
+                location_a, location_b, ..
+            }) => vec![(location_a.clone(), Any), (location_b.clone(), Any)],
+            #[cfg(feature = "insecure")]
+            StrongholdProcedure::CompareSecret(CompareSecret { location, .. }) => vec![(location.clone(), Any)],
+            StrongholdProcedure::WriteVault(_)
+            | StrongholdProcedure::RevokeData(_)
+            | StrongholdProcedure::GarbageCollect(_)
+            | StrongholdProcedure::Slip10Generate(_)
+            | StrongholdProcedure::BIP39Generate(_)
+            | StrongholdProcedure::BIP39Recover(_)
+            | StrongholdProcedure::GenerateKey(_)
+            | StrongholdProcedure::Pbkdf2Hmac(_) => Vec::new(),
+        }
+    }
+}
+
+/// Length that a secret is required to have, to be used as input of a procedure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum InputLength {
+    Any,
+    Exact(usize),
+    AtLeast(usize),
+}
+
+impl InputLength {
+    fn for_key_type(ty: &KeyType) -> Self {
+        match ty {
+            KeyType::Ed25519 => InputLength::AtLeast(ed25519::SECRET_KEY_LENGTH),
+            KeyType::X25519 => InputLength::Exact(x25519::SECRET_KEY_LENGTH),
+        }
+    }
+
+    /// Checks `len` against the requirement and describes the mismatch, if any.
+    pub(crate) fn check(&self, len: usize) -> Result<(), String> {
+        match *self {
+            InputLength::Exact(needs) if len != needs => Err(format!("expected {} bytes, found {}", needs, len)),
+            InputLength::AtLeast(needs) if len < needs => {
+                Err(format!("expected at least {} bytes, found {}", needs, len))
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 /// Implement `StrongholdProcedure: From<T>` for all.
@@ -205,7 +399,9 @@ generic_procedures! {
     UseSecret<2> => { AesKeyWrapEncrypt },
     // Stronghold procedures that implement the `DeriveSecret` trait.
     DeriveSecret<1> => { CopyRecord, Slip10Derive, X25519DiffieHellman, Hkdf, ConcatKdf, AesKeyWrapDecrypt },
-    DeriveSecret<2> => { ConcatSecret }
+    DeriveSecret<2> => { ConcatSecret },
+    // Stronghold procedures that implement the `DeriveSecrets` trait.
+    DeriveSecrets<1> => { Slip10DeriveBatch }
 }
 
 procedures! {
@@ -220,6 +416,10 @@ procedures! {
 pub struct WriteVault {
     pub data: Vec<u8>,
     pub location: Location,
+    /// The kind of the written secret. Defaults to [`RecordKind::Raw`], which is accepted as input
+    /// by all procedures.
+    #[serde(default)]
+    pub kind: Option<RecordKind>,
 }
 
 impl GenerateSecret for WriteVault {
@@ -235,6 +435,10 @@ impl GenerateSecret for WriteVault {
     fn target(&self) -> &Location {
         &self.location
     }
+
+    fn target_kind(&self) -> RecordKind {
+        self.kind.unwrap_or(RecordKind::Raw)
+    }
 }
 
 /// Revoke the data from the specified [`Location`]. Revoked data is not readable and can be
@@ -301,6 +505,10 @@ impl DeriveSecret<1> for CopyRecord {
     fn target(&self) -> &Location {
         &self.target
     }
+
+    fn target_kind(&self, source_kinds: [RecordKind; 1]) -> RecordKind {
+        source_kinds[0]
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -321,6 +529,16 @@ pub enum KeyType {
     X25519,
 }
 
+impl KeyType {
+    /// The [`RecordKind`] of a private key of this type.
+    pub(crate) fn record_kind(&self) -> RecordKind {
+        match self {
+            KeyType::Ed25519 => RecordKind::Ed25519Key,
+            KeyType::X25519 => RecordKind::X25519Key,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Sha2Hash {
     Sha256,
@@ -378,6 +596,10 @@ impl GenerateSecret for BIP39Generate {
     fn target(&self) -> &Location {
         &self.output
     }
+
+    fn target_kind(&self) -> RecordKind {
+        RecordKind::Seed
+    }
 }
 
 impl Drop for BIP39Generate {
@@ -415,6 +637,10 @@ impl GenerateSecret for BIP39Recover {
     fn target(&self) -> &Location {
         &self.output
     }
+
+    fn target_kind(&self) -> RecordKind {
+        RecordKind::Seed
+    }
 }
 
 impl Drop for BIP39Recover {
@@ -454,6 +680,10 @@ impl GenerateSecret for Slip10Generate {
     fn target(&self) -> &Location {
         &self.output
     }
+
+    fn target_kind(&self) -> RecordKind {
+        RecordKind::Seed
+    }
 }
 
 #[derive(GuardDebug, Clone, Serialize, Deserialize)]
@@ -478,14 +708,7 @@ impl DeriveSecret<1> for Slip10Derive {
     type Output = ChainCode;
 
     fn derive(self, guards: [Buffer<u8>; 1]) -> Result<Products<ChainCode>, FatalProcedureError> {
-        let dk = match self.input {
-            Slip10DeriveInput::Key(_) => {
-                slip10::Key::try_from(&*guards[0].borrow()).and_then(|parent| parent.derive(&self.chain))
-            }
-            Slip10DeriveInput::Seed(_) => {
-                slip10::Seed::from_bytes(&guards[0].borrow()).derive(slip10::Curve::Ed25519, &self.chain)
-            }
-        }?;
+        let dk = slip10_derive(&self.input, &guards[0].borrow(), &self.chain)?;
         Ok(Products {
             secret: dk.into(),
             output: dk.chain_code(),
@@ -502,6 +725,104 @@ impl DeriveSecret<1> for Slip10Derive {
     fn target(&self) -> &Location {
         &self.output
     }
+
+    fn source_kinds(&self) -> [Option<RecordKind>; 1] {
+        match &self.input {
+            Slip10DeriveInput::Key(_) => [Some(RecordKind::Ed25519Key)],
+            Slip10DeriveInput::Seed(_) => [Some(RecordKind::Seed)],
+        }
+    }
+
+    fn target_kind(&self, _source_kinds: [RecordKind; 1]) -> RecordKind {
+        RecordKind::Ed25519Key
+    }
+}
+
+/// Derive multiple SLIP10 child keys from the same seed or parent key, store each of them in
+/// the corresponding output location and return their chain codes.
+///
+/// The child keys are written atomically: if any of them can not be derived or stored, none of
+/// them is written.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Slip10DeriveBatch {
+    pub chains: Vec<Chain>,
+
+    pub input: Slip10DeriveInput,
+
+    pub outputs: Vec<Location>,
+}
+
+impl Slip10DeriveBatch {
+    /// Derive a child key for each of the `chains` and store them at consecutive counter
+    /// locations in `vault_path`, starting at `first_counter`.
+    ///
+    /// Fails if the counters of the outputs exceed `usize::MAX`.
+    pub fn counters<V: Into<Vec<u8>>>(
+        chains: Vec<Chain>,
+        input: Slip10DeriveInput,
+        vault_path: V,
+        first_counter: usize,
+    ) -> Result<Self, FatalProcedureError> {
+        let end = first_counter.checked_add(chains.len()).ok_or_else(|| {
+            FatalProcedureError::from(format!(
+                "{} counters starting at {} exceed the counter range",
+                chains.len(),
+                first_counter
+            ))
+        })?;
+        let vault_path = vault_path.into();
+        let outputs = (first_counter..end)
+            .map(|counter| Location::counter(vault_path.clone(), counter))
+            .collect();
+        Ok(Slip10DeriveBatch { chains, input, outputs })
+    }
+}
+
+impl DeriveSecrets<1> for Slip10DeriveBatch {
+    type Output = Vec<ChainCode>;
+
+    fn derive(self, guards: [Buffer<u8>; 1]) -> Result<MultiProducts<Vec<ChainCode>>, FatalProcedureError> {
+        let mut secrets = Vec::with_capacity(self.chains.len());
+        let mut chain_codes = Vec::with_capacity(self.chains.len());
+        for chain in self.chains.iter() {
+            let dk = slip10_derive(&self.input, &guards[0].borrow(), chain)?;
+            chain_codes.push(dk.chain_code());
+            secrets.push(dk.into());
+        }
+        Ok(MultiProducts {
+            secrets,
+            output: chain_codes,
+        })
+    }
+
+    fn source(&self) -> [Location; 1] {
+        match &self.input {
+            Slip10DeriveInput::Key(loc) => [loc.clone()],
+            Slip10DeriveInput::Seed(loc) => [loc.clone()],
+        }
+    }
+
+    fn targets(&self) -> Vec<Location> {
+        self.outputs.clone()
+    }
+
+    fn source_kinds(&self) -> [Option<RecordKind>; 1] {
+        match &self.input {
+            Slip10DeriveInput::Key(_) => [Some(RecordKind::Ed25519Key)],
+            Slip10DeriveInput::Seed(_) => [Some(RecordKind::Seed)],
+        }
+    }
+
+    fn target_kinds(&self, _source_kinds: [RecordKind; 1]) -> Vec<RecordKind> {
+        vec![RecordKind::Ed25519Key; self.outputs.len()]
+    }
+}
+
+fn slip10_derive(input: &Slip10DeriveInput, raw: &[u8], chain: &Chain) -> Result<slip10::Key, crypto::Error> {
+    match input {
+        Slip10DeriveInput::Key(_) => slip10::Key::try_from(raw).and_then(|parent| parent.derive(chain)),
+        Slip10DeriveInput::Seed(_) => slip10::Seed::from_bytes(raw).derive(slip10::Curve::Ed25519, chain),
+    }
 }
 
 fn x25519_secret_key(raw: Ref<u8>) -> Result<x25519::SecretKey, crypto::Error> {
@@ -554,6 +875,10 @@ impl GenerateSecret for GenerateKey {
     fn target(&self) -> &Location {
         &self.output
     }
+
+    fn target_kind(&self) -> RecordKind {
+        self.ty.record_kind()
+    }
 }
 
 /// Derive an Ed25519 public key from the corresponding private key stored at the specified
@@ -584,6 +909,10 @@ impl UseSecret<1> for PublicKey {
     fn source(&self) -> [Location; 1] {
         [self.private_key.clone()]
     }
+
+    fn source_kinds(&self) -> [Option<RecordKind>; 1] {
+        [Some(self.ty.record_kind())]
+    }
 }
 
 /// Use the specified Ed25519 compatible key to sign the given message
@@ -609,6 +938,10 @@ impl UseSecret<1> for Ed25519Sign {
     fn source(&self) -> [Location; 1] {
         [self.private_key.clone()]
     }
+
+    fn source_kinds(&self) -> [Option<RecordKind>; 1] {
+        [Some(RecordKind::Ed25519Key)]
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -641,6 +974,14 @@ impl DeriveSecret<1> for X25519DiffieHellman {
     fn target(&self) -> &Location {
         &self.shared_key
     }
+
+    fn source_kinds(&self) -> [Option<RecordKind>; 1] {
+        [Some(RecordKind::X25519Key)]
+    }
+
+    fn target_kind(&self, _source_kinds: [RecordKind; 1]) -> RecordKind {
+        RecordKind::SymmetricKey
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -726,6 +1067,10 @@ impl DeriveSecret<1> for Hkdf {
     fn target(&self) -> &Location {
         &self.okm
     }
+
+    fn target_kind(&self, _source_kinds: [RecordKind; 1]) -> RecordKind {
+        RecordKind::SymmetricKey
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -768,6 +1113,10 @@ impl GenerateSecret for Pbkdf2Hmac {
     fn target(&self) -> &Location {
         &self.output
     }
+
+    fn target_kind(&self) -> RecordKind {
+        RecordKind::SymmetricKey
+    }
 }
 
 impl Drop for Pbkdf2Hmac {
@@ -823,6 +1172,10 @@ impl UseSecret<1> for AeadEncrypt {
     fn source(&self) -> [Location; 1] {
         [self.key.clone()]
     }
+
+    fn source_kinds(&self) -> [Option<RecordKind>; 1] {
+        [Some(RecordKind::SymmetricKey)]
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -864,6 +1217,10 @@ impl UseSecret<1> for AeadDecrypt {
     fn source(&self) -> [Location; 1] {
         [self.key.clone()]
     }
+
+    fn source_kinds(&self) -> [Option<RecordKind>; 1] {
+        [Some(RecordKind::SymmetricKey)]
+    }
 }
 
 /// Executes the concat KDF as defined in Section 5.8.1 of NIST.800-56A.
@@ -918,6 +1275,10 @@ impl DeriveSecret<1> for ConcatKdf {
     fn target(&self) -> &Location {
         &self.output
     }
+
+    fn target_kind(&self, _source_kinds: [RecordKind; 1]) -> RecordKind {
+        RecordKind::SymmetricKey
+    }
 }
 
 impl ConcatKdf {
@@ -1001,6 +1362,10 @@ impl UseSecret<2> for AesKeyWrapEncrypt {
     fn source(&self) -> [Location; 2] {
         [self.encryption_key.clone(), self.wrap_key.clone()]
     }
+
+    fn source_kinds(&self) -> [Option<RecordKind>; 2] {
+        [Some(RecordKind::SymmetricKey), None]
+    }
 }
 
 impl AesKeyWrapEncrypt {
@@ -1047,6 +1412,10 @@ impl DeriveSecret<1> for AesKeyWrapDecrypt {
     fn target(&self) -> &Location {
         &self.output
     }
+
+    fn source_kinds(&self) -> [Option<RecordKind>; 1] {
+        [Some(RecordKind::SymmetricKey)]
+    }
 }
 
 impl AesKeyWrapDecrypt {
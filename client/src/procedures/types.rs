@@ -1,10 +1,10 @@
 // Copyright 2020-2021 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{FatalEngineError, Location, Provider, RecordError, VaultError};
+use crate::{FatalEngineError, Limits, Location, Provider, RecordError, SizeLimitError, VaultError};
 use engine::{
     runtime::memories::buffer::Buffer,
-    vault::{BoxProvider, VaultId},
+    vault::{BoxProvider, RecordHint, VaultId},
 };
 use serde::{Deserialize, Serialize};
 use std::{fmt::Debug, string::FromUtf8Error};
@@ -22,21 +22,116 @@ pub trait Runner {
         F: FnOnce([Buffer<u8>; N]) -> Result<T, FatalProcedureError>;
 
     // Execute a function that uses the secret stored at `source_locations`. From the returned `Products` the secret is
-    // written into `target_location` and the output is returned.
+    // written into `target_location` as a record of `target_kind` and the output is returned.
     fn exec_proc<F, T, const N: usize>(
         &self,
         source_locations: [Location; N],
         target_location: &Location,
+        target_kind: RecordKind,
         f: F,
     ) -> Result<T, VaultError<FatalProcedureError>>
     where
         F: FnOnce([Buffer<u8>; N]) -> Result<Products<T>, FatalProcedureError>;
 
-    fn write_to_vault(&self, location1: &Location, value: Vec<u8>) -> Result<(), RecordError>;
+    // Execute a function that uses the secrets stored at `source_locations`. Each of the returned secrets is written
+    // into the corresponding target location as a record of the given kind. Either all targets are written, or none.
+    fn exec_proc_multi<F, T, const N: usize>(
+        &self,
+        source_locations: [Location; N],
+        targets: &[(Location, RecordKind)],
+        f: F,
+    ) -> Result<T, VaultError<FatalProcedureError>>
+    where
+        F: FnOnce([Buffer<u8>; N]) -> Result<MultiProducts<T>, FatalProcedureError>;
+
+    fn write_to_vault(&self, location1: &Location, value: Vec<u8>) -> Result<(), RecordError> {
+        self.write_record(location1, value, RecordKind::Raw)
+    }
+
+    // Writes `value` into the vault as a record of the given `kind`.
+    fn write_record(&self, location: &Location, value: Vec<u8>, kind: RecordKind) -> Result<(), RecordError>;
+
+    // Returns the kinds of the records stored at `locations`.
+    fn record_kinds<const N: usize>(
+        &self,
+        locations: &[Location; N],
+    ) -> Result<[RecordKind; N], VaultError<FatalProcedureError>>;
 
     fn revoke_data(&self, location: &Location) -> Result<(), RecordError>;
 
     fn garbage_collect(&self, vault_id: VaultId) -> Result<bool, VaultError<FatalProcedureError>>;
+
+    // Size limits that apply to secrets written by procedures.
+    fn limits(&self) -> Limits;
+}
+
+/// The kind of secret stored in a record. The kind is set when a procedure writes a new
+/// secret, and verified when a procedure uses a secret as input, so that e.g. a SLIP10 seed can
+/// not accidentally be used as an Ed25519 private key.
+///
+/// Secrets that are written directly into a vault, as well as records written by earlier
+/// versions of Stronghold, are of kind [`RecordKind::Raw`], which is accepted by all procedures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RecordKind {
+    /// Untyped secret data.
+    Raw,
+    /// A BIP39 or SLIP10 seed.
+    Seed,
+    /// An Ed25519 private key, or a SLIP10 extended key derived for the Ed25519 curve.
+    Ed25519Key,
+    /// An X25519 private key.
+    X25519Key,
+    /// A symmetric key, e.g. a key derived via KDF or a Diffie-Hellman shared secret.
+    SymmetricKey,
+}
+
+// The kind of a record is stored in its [`RecordHint`]: a fixed prefix followed by the kind.
+// Hints that don't start with the prefix belong to raw records.
+const RECORD_KIND_PREFIX: [u8; 4] = *b"SHRK";
+
+impl RecordKind {
+    fn to_byte(self) -> u8 {
+        match self {
+            RecordKind::Raw => 0,
+            RecordKind::Seed => 1,
+            RecordKind::Ed25519Key => 2,
+            RecordKind::X25519Key => 3,
+            RecordKind::SymmetricKey => 4,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Self {
+        match byte {
+            1 => RecordKind::Seed,
+            2 => RecordKind::Ed25519Key,
+            3 => RecordKind::X25519Key,
+            4 => RecordKind::SymmetricKey,
+            _ => RecordKind::Raw,
+        }
+    }
+
+    /// Creates a [`RecordHint`] for a record of this kind. The remaining bytes of the hint are random.
+    pub(crate) fn to_hint(self) -> RecordHint {
+        let mut hint = [0u8; 24];
+        hint[..RECORD_KIND_PREFIX.len()].copy_from_slice(&RECORD_KIND_PREFIX);
+        hint[RECORD_KIND_PREFIX.len()] = self.to_byte();
+        let random_bytes = stronghold_utils::random::fixed_bytestring(hint.len() - RECORD_KIND_PREFIX.len() - 1);
+        hint[RECORD_KIND_PREFIX.len() + 1..].copy_from_slice(&random_bytes);
+        hint.into()
+    }
+
+    /// Reads the kind of a record from its [`RecordHint`].
+    pub(crate) fn from_hint(hint: &RecordHint) -> Self {
+        match hint.as_ref().split_at(RECORD_KIND_PREFIX.len()) {
+            (prefix, rest) if prefix == RECORD_KIND_PREFIX => Self::from_byte(rest[0]),
+            _ => RecordKind::Raw,
+        }
+    }
+
+    /// Returns `true`, if a record of this kind can be used where `expected` is required.
+    pub fn satisfies(&self, expected: RecordKind) -> bool {
+        *self == expected || *self == RecordKind::Raw
+    }
 }
 
 /// Products of a procedure.
@@ -47,9 +142,17 @@ pub struct Products<T> {
     pub output: T,
 }
 
+/// Products of a procedure that writes multiple secrets.
+pub struct MultiProducts<T> {
+    /// New secrets, one for each target.
+    pub secrets: Vec<Vec<u8>>,
+    /// Non-secret Output.
+    pub output: T,
+}
+
 /// Procedure to create, use or remove secrets from a stronghold vault.
 /// The `primitives::procedure` macro may be used to auto-implement this
-/// trait for procedures that implement `GenerateSecret`, `DeriveSecret` or `UseSecret`.
+/// trait for procedures that implement `GenerateSecret`, `DeriveSecret`, `DeriveSecrets` or `UseSecret`.
 pub trait Procedure: Sized {
     // Non-secret output type.
     type Output: TryFrom<ProcedureOutput>;
@@ -66,11 +169,18 @@ pub trait GenerateSecret: Sized {
 
     fn target(&self) -> &Location;
 
+    /// The kind of the generated secret.
+    fn target_kind(&self) -> RecordKind {
+        RecordKind::Raw
+    }
+
     fn exec<R: Runner>(self, runner: &R) -> Result<Self::Output, ProcedureError> {
         let target = self.target();
         let target = target.clone();
+        let kind = self.target_kind();
         let Products { output, secret } = self.generate()?;
-        runner.write_to_vault(&target, secret)?;
+        runner.limits().check_record_size(secret.len())?;
+        runner.write_record(&target, secret, kind)?;
         Ok(output)
     }
 }
@@ -85,12 +195,92 @@ pub trait DeriveSecret<const N: usize>: Sized {
 
     fn target(&self) -> &Location;
 
+    /// The kinds of records that are accepted as sources. `None` accepts any kind.
+    fn source_kinds(&self) -> [Option<RecordKind>; N] {
+        [None; N]
+    }
+
+    /// The kind of the derived secret, given the kinds of the sources.
+    fn target_kind(&self, _source_kinds: [RecordKind; N]) -> RecordKind {
+        RecordKind::Raw
+    }
+
     fn exec<R: Runner>(self, runner: &R) -> Result<Self::Output, ProcedureError> {
         let sources: [Location; N] = self.source();
+        let found = runner.record_kinds(&sources)?;
+        check_record_kinds(&sources, self.source_kinds(), found)?;
         let target = self.target();
         let target = target.clone();
-        let f = |guard| self.derive(guard);
-        let output = runner.exec_proc(sources, &target, f)?;
+        let target_kind = self.target_kind(found);
+        let limits = runner.limits();
+        let mut exceeded = None;
+        let f = |guard| -> Result<Products<Self::Output>, FatalProcedureError> {
+            let products = self.derive(guard)?;
+            if let Err(e) = limits.check_record_size(products.secret.len()) {
+                exceeded = Some(e.clone());
+                return Err(e.into());
+            }
+            Ok(products)
+        };
+        let output = runner
+            .exec_proc(sources, &target, target_kind, f)
+            .map_err(|e| match exceeded.take() {
+                Some(e) => ProcedureError::SizeLimit(e),
+                None => ProcedureError::from(e),
+            })?;
+        Ok(output)
+    }
+}
+
+/// Trait for procedures that use an existing secret to derive multiple new ones.
+/// The new secrets are written atomically: if any of them can not be written, none of them is stored.
+pub trait DeriveSecrets<const N: usize>: Sized {
+    type Output;
+
+    fn derive(self, guard: [Buffer<u8>; N]) -> Result<MultiProducts<Self::Output>, FatalProcedureError>;
+
+    fn source(&self) -> [Location; N];
+
+    fn targets(&self) -> Vec<Location>;
+
+    /// The kinds of records that are accepted as sources. `None` accepts any kind.
+    fn source_kinds(&self) -> [Option<RecordKind>; N] {
+        [None; N]
+    }
+
+    /// The kinds of the derived secrets, given the kinds of the sources.
+    fn target_kinds(&self, _source_kinds: [RecordKind; N]) -> Vec<RecordKind> {
+        vec![RecordKind::Raw; self.targets().len()]
+    }
+
+    fn exec<R: Runner>(self, runner: &R) -> Result<Self::Output, ProcedureError> {
+        let sources: [Location; N] = self.source();
+        let found = runner.record_kinds(&sources)?;
+        check_record_kinds(&sources, self.source_kinds(), found)?;
+        let targets: Vec<(Location, RecordKind)> = self.targets().into_iter().zip(self.target_kinds(found)).collect();
+        let count = targets.len();
+        let limits = runner.limits();
+        let mut exceeded = None;
+        let f = |guard| -> Result<MultiProducts<Self::Output>, FatalProcedureError> {
+            let products = self.derive(guard)?;
+            if products.secrets.len() != count {
+                let e = format!("derived {} secrets for {} targets", products.secrets.len(), count);
+                return Err(e.into());
+            }
+            for secret in products.secrets.iter() {
+                if let Err(e) = limits.check_record_size(secret.len()) {
+                    exceeded = Some(e.clone());
+                    return Err(e.into());
+                }
+            }
+            Ok(products)
+        };
+        let output = runner
+            .exec_proc_multi(sources, &targets, f)
+            .map_err(|e| match exceeded.take() {
+                Some(e) => ProcedureError::SizeLimit(e),
+                None => ProcedureError::from(e),
+            })?;
         Ok(output)
     }
 }
@@ -103,14 +293,42 @@ pub trait UseSecret<const N: usize>: Sized {
 
     fn source(&self) -> [Location; N];
 
+    /// The kinds of records that are accepted as sources. `None` accepts any kind.
+    fn source_kinds(&self) -> [Option<RecordKind>; N] {
+        [None; N]
+    }
+
     fn exec<R: Runner>(self, runner: &R) -> Result<Self::Output, ProcedureError> {
         let source: [Location; N] = self.source();
+        let found = runner.record_kinds(&source)?;
+        check_record_kinds(&source, self.source_kinds(), found)?;
         let f = |guard| self.use_secret(guard);
         let output = runner.get_guards(source, f)?;
         Ok(output)
     }
 }
 
+/// Verifies that each record at `locations` is of the expected kind.
+fn check_record_kinds<const N: usize>(
+    locations: &[Location; N],
+    expected: [Option<RecordKind>; N],
+    found: [RecordKind; N],
+) -> Result<(), ProcedureError> {
+    for ((location, expected), found) in locations.iter().zip(expected).zip(found) {
+        match expected {
+            Some(expected) if !found.satisfies(expected) => {
+                return Err(ProcedureError::WrongRecordKind {
+                    location: location.clone(),
+                    expected,
+                    found,
+                })
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
 /// Output of a [`StrongholdProcedure`][super::StrongholdProcedure].
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct ProcedureOutput(Vec<u8>);
@@ -139,6 +357,12 @@ impl<const N: usize> From<[u8; N]> for ProcedureOutput {
     }
 }
 
+impl<const N: usize> From<Vec<[u8; N]>> for ProcedureOutput {
+    fn from(v: Vec<[u8; N]>) -> Self {
+        v.concat().into()
+    }
+}
+
 impl From<ProcedureOutput> for () {
     fn from(_: ProcedureOutput) -> Self {}
 }
@@ -164,6 +388,21 @@ impl<const N: usize> TryFrom<ProcedureOutput> for [u8; N] {
     }
 }
 
+impl<const N: usize> TryFrom<ProcedureOutput> for Vec<[u8; N]> {
+    type Error = Vec<u8>;
+
+    fn try_from(value: ProcedureOutput) -> Result<Self, Self::Error> {
+        if value.0.len().checked_rem(N) != Some(0) {
+            return Err(value.0);
+        }
+        Ok(value
+            .0
+            .chunks_exact(N)
+            .map(|chunk| chunk.try_into().expect("chunk has exactly len N"))
+            .collect())
+    }
+}
+
 /// Error on procedure execution.
 #[derive(DeriveError, Debug, Clone, Serialize, Deserialize)]
 pub enum ProcedureError {
@@ -174,6 +413,26 @@ pub enum ProcedureError {
     /// Operation on the vault failed.
     #[error("procedure: {0}")]
     Procedure(#[from] FatalProcedureError),
+
+    /// A new secret exceeded the configured maximum record size.
+    #[error("size limit: {0}")]
+    SizeLimit(#[from] SizeLimitError),
+
+    /// A location that is used as input of the procedure does not contain a record.
+    #[error("missing record at location {0:?}")]
+    MissingRecord(Location),
+
+    /// The secret at a location can not be used as input of the procedure.
+    #[error("invalid input at location {location:?}: {reason}")]
+    InvalidInput { location: Location, reason: String },
+
+    /// The record at a location is not of the kind required by the procedure.
+    #[error("wrong record kind at location {location:?}: expected {expected:?}, found {found:?}")]
+    WrongRecordKind {
+        location: Location,
+        expected: RecordKind,
+        found: RecordKind,
+    },
 }
 
 impl<T> From<VaultError<T>> for ProcedureError
@@ -211,6 +470,12 @@ impl From<String> for FatalProcedureError {
     }
 }
 
+impl From<SizeLimitError> for FatalProcedureError {
+    fn from(e: SizeLimitError) -> Self {
+        FatalProcedureError(e.to_string())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::ProcedureOutput;
@@ -234,6 +499,17 @@ mod test {
         assert_eq!(string, converted);
     }
 
+    #[test]
+    fn proc_io_array_vec() {
+        let arrays: Vec<[u8; 32]> = (0..4).map(|_| random::random()).collect();
+        let proc_io: ProcedureOutput = arrays.clone().into();
+        let converted = Vec::<[u8; 32]>::try_from(proc_io).unwrap();
+        assert_eq!(arrays, converted);
+
+        let proc_io: ProcedureOutput = random::fixed_bytestring(33).into();
+        assert!(Vec::<[u8; 32]>::try_from(proc_io).is_err());
+    }
+
     #[test]
     fn proc_io_array() {
         let mut test_vec = Vec::with_capacity(337);
@@ -9,8 +9,9 @@ use std::{
 };
 
 use crate::{
-    procedures::{GenerateKey, KeyType, StrongholdProcedure},
-    Client, ClientError, ClientVault, KeyProvider, Location, Snapshot, SnapshotPath, Store, Stronghold,
+    procedures::{GenerateKey, KeyType, ProcedureError, PublicKey, StrongholdProcedure},
+    Capabilities, Client, ClientError, ClientVault, KeyProvider, Limits, Location, PreloadEvent, SizeLimitError,
+    Snapshot, SnapshotPath, Store, Stronghold,
 };
 use engine::vault::RecordHint;
 use regex::Replacer;
@@ -341,6 +342,197 @@ fn test_load_client_from_snapshot() {
         .is_ok());
 }
 
+#[test]
+fn test_preload_clients() {
+    let client_path = fixed_random_bytes(32);
+    let missing_client_path = fixed_random_bytes(32);
+    let location = Location::const_generic(fixed_random_bytes(32), fixed_random_bytes(32));
+
+    let filename = base64::encode(fixed_random_bytes(32)).replace('/', "n");
+    let defer = Defer::from((std::env::temp_dir().join(filename), |path: &'_ PathBuf| {
+        let _ = std::fs::remove_file(path);
+    }));
+    let snapshot = SnapshotPath::from_path(&*defer);
+    let key_provider = KeyProvider::try_from(fixed_random_bytes(32)).unwrap();
+
+    let stronghold = Stronghold::default();
+    let client = stronghold.create_client(client_path.clone()).unwrap();
+    client
+        .vault(location.vault_path())
+        .write_secret(location.clone(), fixed_random_bytes(32))
+        .unwrap();
+    stronghold.commit_with_keyprovider(&snapshot, &key_provider).unwrap();
+
+    let stronghold = Stronghold::default();
+    stronghold.load_snapshot(&key_provider, &snapshot).unwrap();
+
+    let events = stronghold.preload_clients(vec![client_path.clone(), missing_client_path.clone()]);
+    match events.recv().unwrap() {
+        PreloadEvent::Ready(path) => assert_eq!(path, client_path),
+        event => panic!("unexpected event {:?}", event),
+    }
+    match events.recv().unwrap() {
+        PreloadEvent::Failed(path, ClientError::ClientDataNotPresent) => assert_eq!(path, missing_client_path),
+        event => panic!("unexpected event {:?}", event),
+    }
+    assert!(events.recv().is_err());
+
+    // the warm client is handed out once
+    let client = stronghold.load_client(client_path.clone()).unwrap();
+    assert!(client.record_exists(&location).unwrap());
+    assert!(matches!(
+        stronghold.load_client(client_path.clone()),
+        Err(ClientError::ClientAlreadyLoaded(_))
+    ));
+
+    // loaded clients are not pre-loaded again
+    let events = stronghold.preload_clients(vec![client_path]);
+    assert!(matches!(
+        events.recv().unwrap(),
+        PreloadEvent::Failed(_, ClientError::ClientAlreadyLoaded(_))
+    ));
+}
+
+#[test]
+fn test_preload_clients_invalidation() {
+    let client_path = fixed_random_bytes(32);
+    let location = Location::const_generic(fixed_random_bytes(32), fixed_random_bytes(32));
+    let new_location = Location::const_generic(fixed_random_bytes(32), fixed_random_bytes(32));
+
+    let filename = base64::encode(fixed_random_bytes(32)).replace('/', "n");
+    let defer = Defer::from((std::env::temp_dir().join(filename), |path: &'_ PathBuf| {
+        let _ = std::fs::remove_file(path);
+    }));
+    let snapshot = SnapshotPath::from_path(&*defer);
+    let key_provider = KeyProvider::try_from(fixed_random_bytes(32)).unwrap();
+
+    let stronghold = Stronghold::default();
+    let client = stronghold.create_client(client_path.clone()).unwrap();
+    client
+        .vault(location.vault_path())
+        .write_secret(location.clone(), fixed_random_bytes(32))
+        .unwrap();
+    stronghold.commit_with_keyprovider(&snapshot, &key_provider).unwrap();
+
+    let stronghold = Stronghold::default();
+    stronghold.load_snapshot(&key_provider, &snapshot).unwrap();
+
+    let events = stronghold.preload_clients(vec![client_path.clone()]);
+    assert!(matches!(events.recv().unwrap(), PreloadEvent::Ready(_)));
+
+    // replacing the client and committing it must drop the pre-loaded state
+    let client = stronghold.create_client(client_path.clone()).unwrap();
+    client
+        .vault(new_location.vault_path())
+        .write_secret(new_location.clone(), fixed_random_bytes(32))
+        .unwrap();
+    stronghold.commit_with_keyprovider(&snapshot, &key_provider).unwrap();
+    stronghold.unload_client(client).unwrap();
+
+    let client = stronghold.load_client(client_path).unwrap();
+    assert!(client.record_exists(&new_location).unwrap());
+    assert!(!client.record_exists(&location).unwrap());
+}
+
+#[test]
+fn test_client_snapshot_files() {
+    let tenant_path = fixed_random_bytes(32);
+    let shared_client_path = fixed_random_bytes(32);
+    let location = Location::const_generic(fixed_random_bytes(32), fixed_random_bytes(32));
+
+    let temp_file = || {
+        let filename = base64::encode(fixed_random_bytes(32)).replace('/', "n");
+        Defer::from((std::env::temp_dir().join(filename), |path: &'_ PathBuf| {
+            let _ = std::fs::remove_file(path);
+        }))
+    };
+    let shared_file = temp_file();
+    let tenant_file = temp_file();
+    let shared_snapshot = SnapshotPath::from_path(&*shared_file);
+    let tenant_snapshot = SnapshotPath::from_path(&*tenant_file);
+    let shared_key = fixed_random_bytes(32);
+    let tenant_key = fixed_random_bytes(32);
+
+    let stronghold = Stronghold::default();
+    stronghold
+        .set_client_snapshot(
+            tenant_path.clone(),
+            tenant_snapshot.clone(),
+            KeyProvider::try_from(tenant_key.clone()).unwrap(),
+        )
+        .unwrap();
+    for client_path in [&tenant_path, &shared_client_path] {
+        let client = stronghold.create_client(client_path).unwrap();
+        client
+            .vault(location.vault_path())
+            .write_secret(location.clone(), fixed_random_bytes(32))
+            .unwrap();
+    }
+    stronghold
+        .commit_with_keyprovider(&shared_snapshot, &KeyProvider::try_from(shared_key.clone()).unwrap())
+        .unwrap();
+    assert!(tenant_snapshot.exists());
+
+    // the shared snapshot does not contain the tenant
+    let stronghold = Stronghold::default();
+    let shared_keyprovider = KeyProvider::try_from(shared_key.clone()).unwrap();
+    stronghold.load_snapshot(&shared_keyprovider, &shared_snapshot).unwrap();
+    assert!(stronghold.load_client(shared_client_path).is_ok());
+    assert!(matches!(
+        stronghold.load_client(tenant_path.clone()),
+        Err(ClientError::ClientDataNotPresent)
+    ));
+
+    // the tenant is loaded from its own file, without the shared snapshot
+    let stronghold = Stronghold::default();
+    stronghold
+        .set_client_snapshot(
+            tenant_path.clone(),
+            tenant_snapshot.clone(),
+            KeyProvider::try_from(tenant_key.clone()).unwrap(),
+        )
+        .unwrap();
+    let client = stronghold.load_client(tenant_path.clone()).unwrap();
+    assert!(client.record_exists(&location).unwrap());
+    assert!(stronghold
+        .remove_client_snapshot(tenant_path.clone())
+        .unwrap()
+        .is_some());
+
+    // clearing the stronghold forgets the designated files
+    stronghold
+        .set_client_snapshot(
+            tenant_path.clone(),
+            tenant_snapshot.clone(),
+            KeyProvider::try_from(tenant_key.clone()).unwrap(),
+        )
+        .unwrap();
+    stronghold.clear().unwrap();
+    assert!(stronghold
+        .remove_client_snapshot(tenant_path.clone())
+        .unwrap()
+        .is_none());
+
+    // the tenant is also found by `load_client_from_snapshot`
+    let stronghold = Stronghold::default();
+    stronghold
+        .set_client_snapshot(
+            tenant_path.clone(),
+            tenant_snapshot.clone(),
+            KeyProvider::try_from(tenant_key).unwrap(),
+        )
+        .unwrap();
+    let client = stronghold
+        .load_client_from_snapshot(tenant_path.clone(), &shared_keyprovider, &shared_snapshot)
+        .unwrap();
+    assert!(client.record_exists(&location).unwrap());
+
+    // purging the tenant deletes its designated file
+    stronghold.purge_client(client).unwrap();
+    assert!(!tenant_snapshot.exists());
+    assert!(stronghold.remove_client_snapshot(tenant_path).unwrap().is_none());
+}
+
 #[test]
 fn test_load_multiple_clients_from_snapshot() {
     let number_of_clients = 10;
@@ -711,3 +903,340 @@ fn test_load_unload_client() {
     assert!(stronghold.unload_client(client).is_ok());
     assert!(stronghold.load_client(client_path).is_ok());
 }
+
+#[test]
+fn test_record_size_limit() {
+    let client_path = fixed_random_bytes(1024);
+    let vault_path = fixed_random_bytes(1024);
+
+    let stronghold = Stronghold::with_limits(Limits::default().with_max_record_size(32));
+    let client = stronghold.create_client(client_path).unwrap();
+    let vault = client.vault(vault_path.clone());
+
+    let location = Location::const_generic(vault_path.clone(), fixed_random_bytes(1024));
+    assert!(vault.write_secret(location.clone(), fixed_random_bytes(32)).is_ok());
+
+    let oversized = Location::const_generic(vault_path.clone(), fixed_random_bytes(1024));
+    let result = vault.write_secret(oversized.clone(), fixed_random_bytes(33));
+    assert!(matches!(
+        result,
+        Err(ClientError::SizeLimitExceeded(SizeLimitError::RecordSize {
+            size: 33,
+            max: 32
+        }))
+    ));
+    assert!(!client.record_exists(&oversized).unwrap());
+
+    // a generated ed25519 key fits, a too restrictive limit is reported by the procedure
+    let stronghold = Stronghold::with_limits(Limits::default().with_max_record_size(16));
+    let client = stronghold.create_client(fixed_random_bytes(1024)).unwrap();
+    let output = Location::const_generic(vault_path, fixed_random_bytes(1024));
+    let result = client.execute_procedure(StrongholdProcedure::GenerateKey(GenerateKey {
+        ty: KeyType::Ed25519,
+        output: output.clone(),
+    }));
+    assert!(matches!(
+        result,
+        Err(ProcedureError::SizeLimit(SizeLimitError::RecordSize { max: 16, .. }))
+    ));
+    assert!(!client.record_exists(&output).unwrap());
+}
+
+#[test]
+fn test_snapshot_size_limit() {
+    let client_path = fixed_random_bytes(1024);
+    let vault_path = fixed_random_bytes(1024);
+
+    let filename = base64::encode(fixed_random_bytes(32));
+    let filename = filename.replace('/', "n");
+    let mut snapshot_path = std::env::temp_dir();
+    snapshot_path.push(filename);
+
+    let defer = Defer::from((snapshot_path, |path: &'_ PathBuf| {
+        let _ = std::fs::remove_file(path);
+    }));
+
+    let snapshot = SnapshotPath::from_path(&*defer);
+    let key_provider = KeyProvider::try_from(fixed_random_bytes(32)).unwrap();
+
+    let stronghold = Stronghold::with_limits(Limits::default().with_max_snapshot_size(1024));
+    let client = stronghold.create_client(client_path).unwrap();
+    client
+        .vault(vault_path.clone())
+        .write_secret(
+            Location::const_generic(vault_path, fixed_random_bytes(1024)),
+            fixed_random_bytes(2048),
+        )
+        .unwrap();
+
+    let result = stronghold.commit_with_keyprovider(&snapshot, &key_provider);
+    assert!(matches!(
+        result,
+        Err(ClientError::SizeLimitExceeded(SizeLimitError::SnapshotSize {
+            max: 1024,
+            ..
+        }))
+    ));
+    assert!(!snapshot.exists());
+
+    // a snapshot written without limits can not be read back with them
+    let stronghold = Stronghold::default();
+    let client = stronghold.create_client(fixed_random_bytes(1024)).unwrap();
+    let vault_path = fixed_random_bytes(1024);
+    client
+        .vault(vault_path.clone())
+        .write_secret(
+            Location::const_generic(vault_path, fixed_random_bytes(1024)),
+            fixed_random_bytes(2048),
+        )
+        .unwrap();
+    stronghold.commit_with_keyprovider(&snapshot, &key_provider).unwrap();
+
+    let limited = Stronghold::with_limits(Limits::default().with_max_snapshot_size(1024));
+    let result = limited.load_snapshot(&key_provider, &snapshot);
+    assert!(matches!(
+        result,
+        Err(ClientError::SizeLimitExceeded(SizeLimitError::SnapshotSize {
+            max: 1024,
+            ..
+        }))
+    ));
+}
+
+#[test]
+fn test_join_all_preserves_per_client_order() {
+    let stronghold = Stronghold::default();
+    let client_paths: Vec<Vec<u8>> = (0..4).map(|_| fixed_random_bytes(32)).collect();
+    for path in &client_paths {
+        stronghold.create_client(path.clone()).unwrap();
+    }
+
+    let vault_path = fixed_random_bytes(32);
+    let mut ops = Vec::new();
+    let mut locations = Vec::new();
+    for i in 0..8 {
+        let location = Location::const_generic(vault_path.clone(), fixed_random_bytes(32));
+        locations.push(location.clone());
+        ops.push((
+            client_paths[i % client_paths.len()].clone(),
+            StrongholdProcedure::GenerateKey(GenerateKey {
+                ty: KeyType::Ed25519,
+                output: location,
+            }),
+        ));
+    }
+    // each public key depends on the key generated by an earlier operation on the same client
+    for (i, location) in locations.iter().enumerate() {
+        ops.push((
+            client_paths[i % client_paths.len()].clone(),
+            StrongholdProcedure::PublicKey(PublicKey {
+                ty: KeyType::Ed25519,
+                private_key: location.clone(),
+            }),
+        ));
+    }
+    // reading a key of another client fails without affecting the other operations
+    ops.push((
+        client_paths[1].clone(),
+        StrongholdProcedure::PublicKey(PublicKey {
+            ty: KeyType::Ed25519,
+            private_key: locations[0].clone(),
+        }),
+    ));
+
+    let results = stronghold.join_all(ops).unwrap();
+    assert_eq!(results.len(), 17);
+    assert!(results[..16].iter().all(|result| result.is_ok()));
+    assert!(results[16].is_err());
+
+    for (i, location) in locations.iter().enumerate() {
+        let client = stronghold
+            .get_client(client_paths[i % client_paths.len()].clone())
+            .unwrap();
+        assert!(client.record_exists(location).unwrap());
+    }
+
+    let unknown = vec![(
+        fixed_random_bytes(32),
+        StrongholdProcedure::PublicKey(PublicKey {
+            ty: KeyType::Ed25519,
+            private_key: locations[0].clone(),
+        }),
+    )];
+    assert!(matches!(
+        stronghold.join_all(unknown),
+        Err(ClientError::ClientDataNotPresent)
+    ));
+}
+
+#[test]
+fn test_delete_vault() {
+    let stronghold = Stronghold::default();
+    let client = stronghold.create_client(fixed_random_bytes(32)).unwrap();
+    let vault_path = fixed_random_bytes(32);
+    let other_vault_path = fixed_random_bytes(32);
+
+    let vault = client.vault(vault_path.clone());
+    let locations: Vec<Location> = (0..4)
+        .map(|_| Location::const_generic(vault_path.clone(), fixed_random_bytes(32)))
+        .collect();
+    for location in &locations {
+        vault.write_secret(location.clone(), fixed_random_bytes(32)).unwrap();
+    }
+    let other = Location::const_generic(other_vault_path.clone(), fixed_random_bytes(32));
+    client
+        .vault(other_vault_path.clone())
+        .write_secret(other.clone(), fixed_random_bytes(32))
+        .unwrap();
+
+    let stats = client.vault_stats(vault_path.clone()).unwrap();
+    assert_eq!(stats.records, 4);
+    assert_eq!(stats, client.vault_stats(vault_path.clone()).unwrap());
+
+    // a modified vault invalidates the token
+    vault
+        .write_secret(locations[0].clone(), fixed_random_bytes(32))
+        .unwrap();
+    assert!(matches!(
+        client.delete_vault(vault_path.clone(), &stats.confirmation),
+        Err(ClientError::ConfirmationMismatch)
+    ));
+    assert!(client.record_exists(&locations[0]).unwrap());
+
+    let stats = client.vault_stats(vault_path.clone()).unwrap();
+    client.delete_vault(vault_path.clone(), &stats.confirmation).unwrap();
+
+    assert!(!client.vault_exists(vault_path.clone()).unwrap());
+    for location in &locations {
+        assert!(!client.record_exists(location).unwrap());
+    }
+    assert!(matches!(
+        client.vault_stats(vault_path),
+        Err(ClientError::VaultNotPresent)
+    ));
+
+    // other vaults are not affected
+    assert!(client.record_exists(&other).unwrap());
+}
+
+#[cfg(feature = "fault-injection")]
+#[test]
+fn test_fault_injection() {
+    use std::time::{Duration, Instant};
+
+    let filename = base64::encode(fixed_random_bytes(32));
+    let filename = filename.replace('/', "n");
+    let mut snapshot_path = std::env::temp_dir();
+    snapshot_path.push(filename);
+
+    let defer = Defer::from((snapshot_path, |path: &'_ PathBuf| {
+        let _ = std::fs::remove_file(path);
+    }));
+
+    let snapshot = SnapshotPath::from_path(&*defer);
+    let key_provider = KeyProvider::try_from(fixed_random_bytes(32)).unwrap();
+
+    let stronghold = Stronghold::default();
+    let faults = stronghold.faults();
+    let client = stronghold.create_client(fixed_random_bytes(32)).unwrap();
+
+    // the next snapshot write fails, the one after succeeds
+    faults.fail_next_snapshot_writes(1);
+    assert!(stronghold.commit_with_keyprovider(&snapshot, &key_provider).is_err());
+    assert!(!snapshot.exists());
+    assert!(stronghold.commit_with_keyprovider(&snapshot, &key_provider).is_ok());
+    assert!(snapshot.exists());
+
+    // procedures are delayed
+    let delay = Duration::from_millis(50);
+    faults.set_procedure_delay(Some(delay));
+    let start = Instant::now();
+    client
+        .execute_procedure(GenerateKey {
+            ty: KeyType::Ed25519,
+            output: Location::const_generic(fixed_random_bytes(32), fixed_random_bytes(32)),
+        })
+        .unwrap();
+    assert!(start.elapsed() >= delay);
+
+    // faults survive a reset of the stronghold and can be removed
+    let stronghold = stronghold.reset();
+    stronghold.faults().fail_next_snapshot_writes(1);
+    faults.reset();
+    assert!(stronghold.commit_with_keyprovider(&snapshot, &key_provider).is_ok());
+}
+
+#[test]
+fn test_self_test() {
+    let report = Stronghold::self_test();
+    assert_eq!(report.checks.len(), 14);
+    assert!(report.passed(), "{:?}", report.failures().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_capabilities() {
+    use crate::procedures::{AeadCipher, AesKeyWrapCipher, Sha2Hash};
+    use serde::{
+        de::value::{Error, StrDeserializer},
+        Deserialize,
+    };
+
+    let capabilities = Stronghold::capabilities();
+    assert!(capabilities.features.iter().any(|feature| feature == "std"));
+
+    #[allow(unused_mut)]
+    let mut procedures = vec![
+        "WriteVault",
+        "RevokeData",
+        "GarbageCollect",
+        "CopyRecord",
+        "Slip10Generate",
+        "Slip10Derive",
+        "Slip10DeriveBatch",
+        "BIP39Generate",
+        "BIP39Recover",
+        "PublicKey",
+        "GenerateKey",
+        "Ed25519Sign",
+        "X25519DiffieHellman",
+        "Hmac",
+        "Hkdf",
+        "ConcatKdf",
+        "AesKeyWrapEncrypt",
+        "AesKeyWrapDecrypt",
+        "Pbkdf2Hmac",
+        "AeadEncrypt",
+        "AeadDecrypt",
+        "ConcatSecret",
+    ];
+    #[cfg(feature = "insecure")]
+    procedures.push("CompareSecret");
+    assert_eq!(capabilities.procedures, procedures);
+    assert_eq!(
+        capabilities.features.iter().any(|feature| feature == "insecure"),
+        capabilities.procedures.iter().any(|name| name == "CompareSecret")
+    );
+
+    // the names of ciphers, key types and hashes are those of their serialized form
+    assert_eq!(capabilities.aead_ciphers, ["Aes256Gcm", "XChaCha20Poly1305"]);
+    assert_eq!(capabilities.key_wrap_ciphers, ["Aes256"]);
+    assert_eq!(capabilities.key_types, ["Ed25519", "X25519"]);
+    assert_eq!(capabilities.hashes, ["Sha256", "Sha384", "Sha512"]);
+    for name in &capabilities.aead_ciphers {
+        assert!(AeadCipher::deserialize(StrDeserializer::<Error>::new(name)).is_ok());
+    }
+    for name in &capabilities.key_wrap_ciphers {
+        assert!(AesKeyWrapCipher::deserialize(StrDeserializer::<Error>::new(name)).is_ok());
+    }
+    for name in &capabilities.key_types {
+        assert!(KeyType::deserialize(StrDeserializer::<Error>::new(name)).is_ok());
+    }
+    for name in &capabilities.hashes {
+        assert!(Sha2Hash::deserialize(StrDeserializer::<Error>::new(name)).is_ok());
+    }
+
+    assert_eq!(capabilities.snapshot_version, engine::snapshot::VERSION);
+
+    let serialized = bincode::serialize(&capabilities).unwrap();
+    assert_eq!(bincode::deserialize::<Capabilities>(&serialized).unwrap(), capabilities);
+}
@@ -10,11 +10,12 @@ use crate::{
     procedures::{
         AeadCipher, AeadDecrypt, AeadEncrypt, AesKeyWrapCipher, AesKeyWrapDecrypt, AesKeyWrapEncrypt, BIP39Generate,
         BIP39Recover, ConcatKdf, CopyRecord, DeriveSecret, Ed25519Sign, GenerateKey, GenerateSecret, Hkdf, KeyType,
-        MnemonicLanguage, PublicKey, Sha2Hash, Slip10Derive, Slip10DeriveInput, Slip10Generate, StrongholdProcedure,
-        WriteVault, X25519DiffieHellman,
+        MnemonicLanguage, ProcedureError, PublicKey, RecordKind, RevokeData, Runner, Sha2Hash, Slip10Derive,
+        Slip10DeriveBatch, Slip10DeriveInput, Slip10Generate, StrongholdProcedure, WriteVault, X25519DiffieHellman,
     },
+    sync::{MergePolicy, SyncClientsConfig},
     tests::fresh,
-    Client, Location, Stronghold,
+    Client, KeyProvider, LoadFromPath, Location, Provider, SnapshotPath, SnapshotState, Stronghold,
 };
 
 use crypto::{
@@ -22,6 +23,11 @@ use crypto::{
     keys::slip10::ChainCode,
     signatures::ed25519,
 };
+use engine::{
+    store::Cache,
+    vault::{ClientId, DbView, Key as PKey, VaultId},
+};
+use std::collections::HashMap;
 use stronghold_utils::random;
 
 #[test]
@@ -133,6 +139,7 @@ fn test_concat_kdf_with_jwa() {
             106, 72, 246, 218, 167, 121, 140, 254, 144, 196,
         ],
         location: secret_location,
+        kind: None,
     };
 
     let key_len: usize = 16;
@@ -190,6 +197,7 @@ fn test_aes_256_keywrap_roundtrip() {
         .execute_procedure(WriteVault {
             data: encryption_key,
             location: encryption_key_location.clone(),
+            kind: None,
         })
         .unwrap();
 
@@ -197,6 +205,7 @@ fn test_aes_256_keywrap_roundtrip() {
         .execute_procedure(WriteVault {
             data: plaintext.clone(),
             location: wrap_key_location.clone(),
+            kind: None,
         })
         .unwrap();
 
@@ -823,11 +832,13 @@ fn test_usecase_concatkdf() {
         WriteVault {
             data: b"abcdefg".to_vec(),
             location: location_a.clone(),
+            kind: None,
         }
         .into(),
         WriteVault {
             data: b"hijklmn".to_vec(),
             location: location_b.clone(),
+            kind: None,
         }
         .into(),
     ]);
@@ -852,3 +863,523 @@ fn test_usecase_concatkdf() {
     let result = result.unwrap();
     assert!(result[0] == 1, "failed: ({:?})", result);
 }
+
+#[test]
+fn test_validate_procedure() {
+    let stronghold: Stronghold = Stronghold::default();
+    let client: Client = stronghold.create_client(b"client_path").unwrap();
+
+    let key_location = fresh::location();
+    let generate = GenerateKey {
+        ty: KeyType::X25519,
+        output: key_location.clone(),
+    };
+    let public_key = PublicKey {
+        ty: KeyType::X25519,
+        private_key: key_location.clone(),
+    };
+
+    // the key does not exist yet
+    assert!(matches!(
+        client.validate_procedure(public_key.clone()),
+        Err(ProcedureError::MissingRecord(_))
+    ));
+
+    // but is produced by an earlier procedure of the chain
+    let chain: Vec<StrongholdProcedure> = vec![generate.clone().into(), public_key.clone().into()];
+    assert!(client.validate_procedure_chained(&chain).is_ok());
+
+    // validation does not execute anything
+    assert!(!client.record_exists(&key_location).unwrap());
+
+    // revoked records are missing for later procedures
+    let revoke = RevokeData {
+        location: key_location.clone(),
+        should_gc: false,
+    };
+    let chain: Vec<StrongholdProcedure> = vec![generate.into(), revoke.into(), public_key.into()];
+    assert!(matches!(
+        client.validate_procedure_chained(&chain),
+        Err(ProcedureError::MissingRecord(_))
+    ));
+
+    // a record of the wrong length is rejected
+    let raw_location = fresh::location();
+    client
+        .execute_procedure(WriteVault {
+            data: random::fixed_bytestring(64),
+            location: raw_location.clone(),
+            kind: None,
+        })
+        .unwrap();
+    let dh = X25519DiffieHellman {
+        public_key: [0u8; 32],
+        private_key: raw_location.clone(),
+        shared_key: fresh::location(),
+    };
+    assert!(matches!(
+        client.validate_procedure(dh),
+        Err(ProcedureError::InvalidInput { .. })
+    ));
+    let sign = Ed25519Sign {
+        msg: b"message".to_vec(),
+        private_key: raw_location,
+    };
+    assert!(client.validate_procedure(sign).is_ok());
+
+    // a SLIP10 parent key consists of the chain code and the private key
+    let key_location = fresh::location();
+    client
+        .execute_procedure(WriteVault {
+            data: random::fixed_bytestring(32),
+            location: key_location.clone(),
+            kind: None,
+        })
+        .unwrap();
+    let derive = Slip10Derive {
+        chain: fresh::hd_path().1,
+        input: Slip10DeriveInput::Key(key_location),
+        output: fresh::location(),
+    };
+    assert!(matches!(
+        client.validate_procedure(derive),
+        Err(ProcedureError::InvalidInput { .. })
+    ));
+}
+
+#[test]
+fn test_chain_rollback() {
+    use crate::procedures::ConcatSecret;
+
+    let stronghold: Stronghold = Stronghold::default();
+    let client: Client = stronghold.create_client(b"client_path").unwrap();
+
+    let location_a = fresh::location();
+    let location_b = fresh::location();
+    let concat = fresh::location();
+    let missing = fresh::location();
+
+    // the outputs of all procedures that ran before the failing one are revoked, including
+    // those of `ConcatSecret`
+    let result = client.execute_procedure_chained(vec![
+        WriteVault {
+            data: b"abcdefg".to_vec(),
+            location: location_a.clone(),
+            kind: None,
+        }
+        .into(),
+        WriteVault {
+            data: b"hijklmn".to_vec(),
+            location: location_b.clone(),
+            kind: None,
+        }
+        .into(),
+        ConcatSecret {
+            location_a,
+            location_b,
+            output_location: concat.clone(),
+        }
+        .into(),
+        Ed25519Sign {
+            msg: b"message".to_vec(),
+            private_key: missing,
+        }
+        .into(),
+    ]);
+    assert!(result.is_err());
+    assert!(matches!(
+        client.execute_procedure(CopyRecord {
+            source: concat,
+            target: fresh::location(),
+        }),
+        Err(ProcedureError::Engine(_))
+    ));
+}
+
+#[test]
+fn test_record_kinds() {
+    let stronghold: Stronghold = Stronghold::default();
+    let client: Client = stronghold.create_client(b"client_path").unwrap();
+
+    let x25519_key = fresh::location();
+    client
+        .execute_procedure(GenerateKey {
+            ty: KeyType::X25519,
+            output: x25519_key.clone(),
+        })
+        .unwrap();
+
+    let sign = Ed25519Sign {
+        msg: b"message".to_vec(),
+        private_key: x25519_key.clone(),
+    };
+    assert!(matches!(
+        client.validate_procedure(sign.clone()),
+        Err(ProcedureError::WrongRecordKind {
+            expected: RecordKind::Ed25519Key,
+            found: RecordKind::X25519Key,
+            ..
+        })
+    ));
+    assert!(matches!(
+        client.execute_procedure(sign),
+        Err(ProcedureError::WrongRecordKind {
+            expected: RecordKind::Ed25519Key,
+            found: RecordKind::X25519Key,
+            ..
+        })
+    ));
+
+    // the kind of records produced earlier in a chain is checked as well
+    let key = fresh::location();
+    let chain: Vec<StrongholdProcedure> = vec![
+        GenerateKey {
+            ty: KeyType::X25519,
+            output: key.clone(),
+        }
+        .into(),
+        Ed25519Sign {
+            msg: b"message".to_vec(),
+            private_key: key,
+        }
+        .into(),
+    ];
+    assert!(matches!(
+        client.validate_procedure_chained(&chain),
+        Err(ProcedureError::WrongRecordKind {
+            expected: RecordKind::Ed25519Key,
+            found: RecordKind::X25519Key,
+            ..
+        })
+    ));
+
+    // a copied record keeps its kind
+    let copy = fresh::location();
+    client
+        .execute_procedure(CopyRecord {
+            source: x25519_key,
+            target: copy.clone(),
+        })
+        .unwrap();
+    let sign = Ed25519Sign {
+        msg: b"message".to_vec(),
+        private_key: copy,
+    };
+    assert!(matches!(
+        client.execute_procedure(sign),
+        Err(ProcedureError::WrongRecordKind { .. })
+    ));
+
+    // a seed is not a key
+    let seed = fresh::location();
+    client
+        .execute_procedure(Slip10Generate {
+            size_bytes: None,
+            output: seed.clone(),
+        })
+        .unwrap();
+    let sign = Ed25519Sign {
+        msg: b"message".to_vec(),
+        private_key: seed.clone(),
+    };
+    assert!(matches!(
+        client.execute_procedure(sign),
+        Err(ProcedureError::WrongRecordKind {
+            expected: RecordKind::Ed25519Key,
+            found: RecordKind::Seed,
+            ..
+        })
+    ));
+    let derive = Slip10Derive {
+        chain: fresh::hd_path().1,
+        input: Slip10DeriveInput::Key(seed),
+        output: fresh::location(),
+    };
+    assert!(matches!(
+        client.execute_procedure(derive),
+        Err(ProcedureError::WrongRecordKind {
+            expected: RecordKind::Ed25519Key,
+            found: RecordKind::Seed,
+            ..
+        })
+    ));
+
+    // raw records are accepted by all procedures
+    let raw = fresh::location();
+    client
+        .execute_procedure(WriteVault {
+            data: random::fixed_bytestring(32),
+            location: raw.clone(),
+            kind: None,
+        })
+        .unwrap();
+    let sign = Ed25519Sign {
+        msg: b"message".to_vec(),
+        private_key: raw,
+    };
+    assert!(client.execute_procedure(sign).is_ok());
+
+    // secrets written directly into a vault can be given a kind
+    let typed = fresh::location();
+    client
+        .execute_procedure(WriteVault {
+            data: random::fixed_bytestring(32),
+            location: typed.clone(),
+            kind: Some(RecordKind::X25519Key),
+        })
+        .unwrap();
+    let sign = Ed25519Sign {
+        msg: b"message".to_vec(),
+        private_key: typed,
+    };
+    assert!(matches!(
+        client.execute_procedure(sign),
+        Err(ProcedureError::WrongRecordKind {
+            found: RecordKind::X25519Key,
+            ..
+        })
+    ));
+    let typed = fresh::location();
+    client
+        .vault(typed.vault_path())
+        .write_secret_with_kind(typed.clone(), random::fixed_bytestring(32), RecordKind::Seed)
+        .unwrap();
+    assert_eq!(client.record_kinds(&[typed]).unwrap(), [RecordKind::Seed]);
+}
+
+#[test]
+fn test_slip10_derive_batch() {
+    let stronghold: Stronghold = Stronghold::default();
+    let client: Client = stronghold.create_client(b"client_path").unwrap();
+
+    let seed = fresh::location();
+    client
+        .execute_procedure(Slip10Generate {
+            size_bytes: None,
+            output: seed.clone(),
+        })
+        .unwrap();
+
+    let chains: Vec<_> = (0..3).map(|_| fresh::hd_path().1).collect();
+    let vault_path = random::fixed_bytestring(16);
+    let batch = Slip10DeriveBatch::counters(
+        chains.clone(),
+        Slip10DeriveInput::Seed(seed.clone()),
+        vault_path.clone(),
+        0,
+    )
+    .unwrap();
+    let chain_codes: Vec<ChainCode> = client.execute_procedure(batch).unwrap();
+    assert_eq!(chain_codes.len(), chains.len());
+
+    // each child is the same as when derived on its own
+    for (counter, (chain, chain_code)) in chains.into_iter().zip(chain_codes).enumerate() {
+        let derived = Location::counter(vault_path.clone(), counter);
+        let single = Slip10Derive {
+            chain,
+            input: Slip10DeriveInput::Seed(seed.clone()),
+            output: fresh::location(),
+        };
+        assert_eq!(client.execute_procedure(single.clone()).unwrap(), chain_code);
+
+        let pk_batch = client
+            .execute_procedure(PublicKey {
+                ty: KeyType::Ed25519,
+                private_key: derived,
+            })
+            .unwrap();
+        let pk_single = client
+            .execute_procedure(PublicKey {
+                ty: KeyType::Ed25519,
+                private_key: single.output,
+            })
+            .unwrap();
+        assert_eq!(pk_batch, pk_single);
+    }
+
+    // if the batch fails, none of the children is written
+    let vault_path = random::fixed_bytestring(16);
+    let mut batch = Slip10DeriveBatch::counters(
+        vec![fresh::hd_path().1, fresh::hd_path().1],
+        Slip10DeriveInput::Seed(seed),
+        vault_path.clone(),
+        0,
+    )
+    .unwrap();
+    batch.outputs.push(Location::counter(vault_path.clone(), 2usize));
+    assert!(client.execute_procedure(batch).is_err());
+    assert!(!client.vault_exists(&vault_path).unwrap());
+
+    // the counters of the outputs must not overflow
+    assert!(Slip10DeriveBatch::counters(
+        vec![fresh::hd_path().1, fresh::hd_path().1],
+        Slip10DeriveInput::Seed(fresh::location()),
+        vault_path,
+        usize::MAX,
+    )
+    .is_err());
+}
+
+#[test]
+fn test_derivation_tree() {
+    let stronghold: Stronghold = Stronghold::default();
+    let client: Client = stronghold.create_client(b"client_path").unwrap();
+
+    let seed = fresh::location();
+    client
+        .execute_procedure(Slip10Generate {
+            size_bytes: None,
+            output: seed.clone(),
+        })
+        .unwrap();
+
+    let vault_path = random::fixed_bytestring(16);
+    let account = Location::generic(vault_path.clone(), b"account".to_vec());
+    let (_, account_chain) = fresh::hd_path();
+    client
+        .execute_procedure(Slip10Derive {
+            chain: account_chain,
+            input: Slip10DeriveInput::Seed(seed.clone()),
+            output: account.clone(),
+        })
+        .unwrap();
+    let chains: Vec<_> = (0..2).map(|_| fresh::hd_path().1).collect();
+    client
+        .execute_procedure(
+            Slip10DeriveBatch::counters(chains, Slip10DeriveInput::Key(account.clone()), vault_path.clone(), 0)
+                .unwrap(),
+        )
+        .unwrap();
+
+    let tree = client.derivation_tree(&vault_path).unwrap();
+    assert_eq!(tree.len(), 3);
+    assert!(client.derivation_tree(random::fixed_bytestring(16)).unwrap().is_empty());
+
+    // the derivation paths reproduce the stored keys
+    for entry in tree.iter() {
+        let input = if entry.source.resolve() == seed.resolve() {
+            Slip10DeriveInput::Seed(entry.source.clone())
+        } else {
+            assert_eq!(entry.source.resolve(), account.resolve());
+            Slip10DeriveInput::Key(entry.source.clone())
+        };
+        let rederived = fresh::location();
+        client
+            .execute_procedure(Slip10Derive {
+                chain: entry.chain.clone(),
+                input,
+                output: rederived.clone(),
+            })
+            .unwrap();
+        let pk = |private_key: Location| {
+            client
+                .execute_procedure(PublicKey {
+                    ty: KeyType::Ed25519,
+                    private_key,
+                })
+                .unwrap()
+        };
+        assert_eq!(pk(entry.location.clone()), pk(rederived));
+    }
+
+    // revoked records are omitted
+    client
+        .execute_procedure(RevokeData {
+            location: account.clone(),
+            should_gc: false,
+        })
+        .unwrap();
+    let tree = client.derivation_tree(&vault_path).unwrap();
+    assert_eq!(tree.len(), 2);
+    assert!(tree.iter().all(|entry| entry.location.resolve() != account.resolve()));
+
+    // overwritten records are omitted
+    let overwritten = tree[0].location.clone();
+    client
+        .vault(&vault_path)
+        .write_secret(overwritten.clone(), random::fixed_bytestring(32))
+        .unwrap();
+    let tree = client.derivation_tree(&vault_path).unwrap();
+    assert_eq!(tree.len(), 1);
+    assert!(tree
+        .iter()
+        .all(|entry| entry.location.resolve() != overwritten.resolve()));
+
+    // the tree is not part of the store
+    assert!(client.store().keys().unwrap().is_empty());
+    client.store().clear().unwrap();
+    assert_eq!(client.derivation_tree(&vault_path).unwrap().len(), 1);
+
+    // the tree does not occupy the user key space
+    let reserved_key = b"stronghold.derivation_tree".to_vec();
+    client
+        .store()
+        .insert(reserved_key.clone(), b"user data".to_vec(), None)
+        .unwrap();
+
+    // the tree is persisted with the client
+    let snapshot_path = std::env::temp_dir().join(base64::encode(random::fixed_bytestring(32)).replace('/', "n"));
+    let snapshot = SnapshotPath::from_path(&snapshot_path);
+    let key_provider = KeyProvider::try_from(random::fixed_bytestring(32)).unwrap();
+    stronghold.commit_with_keyprovider(&snapshot, &key_provider).unwrap();
+
+    let stronghold = Stronghold::default();
+    let client = stronghold
+        .load_client_from_snapshot(b"client_path", &key_provider, &snapshot)
+        .unwrap();
+    let _ = std::fs::remove_file(&snapshot_path);
+    assert_eq!(client.derivation_tree(&vault_path).unwrap().len(), 1);
+    assert_eq!(client.store().keys().unwrap(), vec![reserved_key.clone()]);
+    assert_eq!(client.store().get(&reserved_key).unwrap(), Some(b"user data".to_vec()));
+
+    // snapshot states written without derivation trees remain readable
+    let client_id = ClientId::load_from_path(b"client_path", b"client_path");
+    type LegacyClientState = (
+        HashMap<VaultId, PKey<Provider>>,
+        DbView<Provider>,
+        Cache<Vec<u8>, Vec<u8>>,
+    );
+    let legacy: HashMap<ClientId, LegacyClientState> = HashMap::from([(client_id, Default::default())]);
+    let state: SnapshotState = bincode::deserialize(&bincode::serialize(&legacy).unwrap()).unwrap();
+    assert!(state.0[&client_id].3.is_empty());
+}
+
+#[test]
+fn test_derivation_tree_sync() {
+    let stronghold: Stronghold = Stronghold::default();
+    let client: Client = stronghold.create_client(b"client_path").unwrap();
+
+    let seed = fresh::location();
+    client
+        .execute_procedure(Slip10Generate {
+            size_bytes: None,
+            output: seed.clone(),
+        })
+        .unwrap();
+    let vault_path = random::fixed_bytestring(16);
+    let derived = Location::generic(vault_path.clone(), b"account".to_vec());
+    let kept = Location::generic(vault_path.clone(), b"kept".to_vec());
+    for output in [derived.clone(), kept.clone()] {
+        client
+            .execute_procedure(Slip10Derive {
+                chain: fresh::hd_path().1,
+                input: Slip10DeriveInput::Seed(seed.clone()),
+                output,
+            })
+            .unwrap();
+    }
+    assert_eq!(client.derivation_tree(&vault_path).unwrap().len(), 2);
+
+    // a record synced over a derived record is no longer part of the tree
+    let other: Client = stronghold.create_client(b"other_client_path").unwrap();
+    other
+        .vault(&vault_path)
+        .write_secret(derived.clone(), random::fixed_bytestring(32))
+        .unwrap();
+    client
+        .sync_with(&other, SyncClientsConfig::new(MergePolicy::Replace))
+        .unwrap();
+    let tree = client.derivation_tree(&vault_path).unwrap();
+    assert_eq!(tree.len(), 1);
+    assert_eq!(tree[0].location.resolve(), kept.resolve());
+}
@@ -1,7 +1,8 @@
 // Copyright 2020-2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{ClientError, Store};
+use crate::{ClientError, Limits, SizeLimitError, Store, Stronghold};
+use std::time::Duration;
 use stronghold_utils::random as rand;
 
 #[test]
@@ -56,6 +57,34 @@ fn test_contains_key() -> Result<(), ClientError> {
     Ok(())
 }
 
+#[test]
+fn test_touch() -> Result<(), ClientError> {
+    let store = Store::default();
+    let key = b"some key";
+    let data = b"some data".to_vec();
+
+    assert_eq!(store.lifetime(key)?, None);
+    assert!(!store.touch(key, None)?);
+
+    store.insert(key.to_vec(), data.clone(), Some(Duration::from_secs(1)))?;
+    assert!(store.lifetime(key)?.flatten().unwrap() <= Duration::from_secs(1));
+
+    // extending the lifetime keeps the value
+    assert!(store.touch(key, Some(Duration::from_secs(3600)))?);
+    assert!(store.lifetime(key)?.flatten().unwrap() > Duration::from_secs(1));
+    assert_eq!(store.get(key)?, Some(data));
+
+    assert!(store.touch(key, None)?);
+    assert_eq!(store.lifetime(key)?, Some(None));
+
+    // expired values can not be touched
+    store.insert(key.to_vec(), b"expired".to_vec(), Some(Duration::default()))?;
+    assert!(!store.touch(key, None)?);
+    assert!(store.get(key)?.is_none());
+
+    Ok(())
+}
+
 #[test]
 fn test_keys() {
     let store = Store::default();
@@ -81,3 +110,21 @@ fn test_keys() {
 
     assert_eq!(actual, keys);
 }
+
+#[test]
+fn test_store_value_size_limit() {
+    let stronghold = Stronghold::with_limits(Limits::default().with_max_store_value_size(16));
+    let store = stronghold.store();
+
+    assert!(store.insert(b"small".to_vec(), vec![0u8; 16], None).is_ok());
+
+    let result = store.insert(b"large".to_vec(), vec![0u8; 17], None);
+    assert!(matches!(
+        result,
+        Err(ClientError::SizeLimitExceeded(SizeLimitError::StoreValueSize {
+            size: 17,
+            max: 16
+        }))
+    ));
+    assert!(!store.contains_key(b"large").unwrap());
+}
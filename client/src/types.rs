@@ -4,18 +4,28 @@
 //! A collection of relevant interface types to interact with a Stronghold
 
 // modules
+mod capabilities;
 mod client;
 mod error;
+#[cfg(feature = "fault-injection")]
+mod fault;
+mod limits;
 mod location;
+mod self_test;
 mod snapshot;
 mod store;
 mod stronghold;
 mod vault;
 
 // re-export imports
+pub use capabilities::*;
 pub use client::*;
 pub use error::*;
+#[cfg(feature = "fault-injection")]
+pub use fault::*;
+pub use limits::*;
 pub use location::*;
+pub use self_test::{SelfTestCheck, SelfTestReport};
 pub use snapshot::*;
 pub use store::*;
 pub use stronghold::*;
@@ -0,0 +1,128 @@
+// Copyright 2020-2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::procedures::{AeadCipher, AesKeyWrapCipher, KeyType, Sha2Hash, StrongholdProcedure};
+use engine::{runtime::check_memory_protection, snapshot};
+use serde::{
+    de::{self, value, DeserializeOwned, Visitor},
+    forward_to_deserialize_any, Deserialize, Deserializer, Serialize,
+};
+
+/// Describes what a build of Stronghold supports, see [`crate::Stronghold::capabilities`].
+///
+/// The manifest is serializable, so that it can be handed to other applications, which can then
+/// adapt to the features of this build instead of probing for errors.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capabilities {
+    /// Version of the `iota-stronghold` crate.
+    pub version: String,
+
+    /// Cargo features of the `iota-stronghold` crate that were enabled at compile time.
+    pub features: Vec<String>,
+
+    /// Names of the supported [`StrongholdProcedure`]s, as used in their serialized form.
+    pub procedures: Vec<String>,
+
+    /// Ciphers supported by [`crate::procedures::AeadEncrypt`] and [`crate::procedures::AeadDecrypt`].
+    pub aead_ciphers: Vec<String>,
+
+    /// Ciphers supported by [`crate::procedures::AesKeyWrapEncrypt`] and
+    /// [`crate::procedures::AesKeyWrapDecrypt`].
+    pub key_wrap_ciphers: Vec<String>,
+
+    /// Types of keys that can be generated with [`crate::procedures::GenerateKey`].
+    pub key_types: Vec<String>,
+
+    /// Hash functions supported by [`crate::procedures::Hmac`], [`crate::procedures::Hkdf`],
+    /// [`crate::procedures::ConcatKdf`] and [`crate::procedures::Pbkdf2Hmac`].
+    pub hashes: Vec<String>,
+
+    /// Version of the snapshot file format that is read and written.
+    pub snapshot_version: [u8; 2],
+
+    /// Runtime protections of the platform Stronghold is running on.
+    pub runtime: RuntimeCapabilities,
+}
+
+/// Runtime protections of the current platform, as part of [`Capabilities`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeCapabilities {
+    /// Operating system, as in [`std::env::consts::OS`].
+    pub os: String,
+
+    /// CPU architecture, as in [`std::env::consts::ARCH`].
+    pub arch: String,
+
+    /// `true`, if guarded memory can be locked into RAM and access protected on this platform.
+    pub memory_protection: bool,
+}
+
+impl Capabilities {
+    /// Collects the capabilities of this build on the current platform.
+    pub(crate) fn current() -> Self {
+        let mut features = Vec::new();
+        if cfg!(feature = "std") {
+            features.push("std");
+        }
+        if cfg!(feature = "insecure") {
+            features.push("insecure");
+        }
+        if cfg!(feature = "fault-injection") {
+            features.push("fault-injection");
+        }
+
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            features: to_strings(&features),
+            procedures: to_strings(variant_names::<StrongholdProcedure>()),
+            aead_ciphers: to_strings(variant_names::<AeadCipher>()),
+            key_wrap_ciphers: to_strings(variant_names::<AesKeyWrapCipher>()),
+            key_types: to_strings(variant_names::<KeyType>()),
+            hashes: to_strings(variant_names::<Sha2Hash>()),
+            snapshot_version: snapshot::VERSION,
+            runtime: RuntimeCapabilities {
+                os: std::env::consts::OS.to_string(),
+                arch: std::env::consts::ARCH.to_string(),
+                memory_protection: check_memory_protection().is_ok(),
+            },
+        }
+    }
+}
+
+fn to_strings(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+/// Returns the names of the variants of the enum `T` that are compiled into this build, as used in
+/// its serialized form. The names are taken from the derived [`Deserialize`] implementation, which
+/// passes them to [`Deserializer::deserialize_enum`].
+fn variant_names<T: DeserializeOwned>() -> &'static [&'static str] {
+    struct VariantNames<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de, 'a> Deserializer<'de> for VariantNames<'a> {
+        type Error = value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+            Err(de::Error::custom("expected an enum"))
+        }
+
+        fn deserialize_enum<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            variants: &'static [&'static str],
+            _visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            *self.0 = variants;
+            Err(de::Error::custom("collected variant names"))
+        }
+
+        forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit
+            unit_struct newtype_struct seq tuple tuple_struct map struct identifier ignored_any
+        }
+    }
+
+    let mut names: &'static [&'static str] = &[];
+    let _ = T::deserialize(VariantNames(&mut names));
+    names
+}
@@ -5,18 +5,23 @@ use super::{location, snapshot};
 use crate::{
     derive_vault_id,
     procedures::{
-        FatalProcedureError, Procedure, ProcedureError, ProcedureOutput, Products, Runner, StrongholdProcedure,
+        FatalProcedureError, Procedure, ProcedureError, ProcedureOutput, Products, RecordKind, RevokeData, Runner,
+        StrongholdProcedure,
     },
     sync::{KeyProvider, MergePolicy, SyncClients, SyncClientsConfig, SyncSnapshots, SyncSnapshotsConfig},
-    ClientError, ClientState, ClientVault, KeyStore, Location, Provider, RecordError, SnapshotError, Store, Stronghold,
+    ClientError, ClientState, ClientVault, DerivationTree, DerivedRecord, KeyStore, Limits, Location, Provider,
+    RecordError, SnapshotError, Store, Stronghold, VaultStats,
+};
+use crypto::{
+    hashes::{blake2b::Blake2b256, Digest},
+    keys::x25519,
 };
-use crypto::keys::x25519;
 use engine::{
     runtime::memories::buffer::Buffer,
-    vault::{view::Record, BoxProvider, ClientId, DbView, Id, Key, RecordHint, RecordId, VaultId},
+    vault::{view::Record, BoxProvider, ChainId, ClientId, DbView, Id, Key, RecordHint, RecordId, VaultId},
 };
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     error::Error,
     sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
     time::Duration,
@@ -37,6 +42,16 @@ pub struct Client {
 
     // Contains the Record Ids for the most recent Record in each vault.
     pub store: Store,
+
+    // Records derived via SLIP10 per vault
+    pub(crate) derivation_tree: Arc<RwLock<DerivationTree>>,
+
+    // Size limits inherited from the owning Stronghold
+    pub(crate) limits: Limits,
+
+    // Faults injected by the owning Stronghold
+    #[cfg(feature = "fault-injection")]
+    pub(crate) faults: crate::FaultInjector,
 }
 
 impl Default for Client {
@@ -46,6 +61,10 @@ impl Default for Client {
             db: Arc::new(RwLock::new(DbView::new())),
             id: ClientId::default(),
             store: Store::default(),
+            derivation_tree: Default::default(),
+            limits: Limits::default(),
+            #[cfg(feature = "fault-injection")]
+            faults: Default::default(),
         }
     }
 }
@@ -95,6 +114,168 @@ impl Client {
         Ok(contains_record)
     }
 
+    /// Returns the [`VaultStats`] of the vault at `vault_path`.
+    ///
+    /// # Example
+    /// ```
+    /// use iota_stronghold::{Location, Stronghold};
+    ///
+    /// let stronghold = Stronghold::default();
+    /// let client = stronghold.create_client(b"client").unwrap();
+    /// let vault = client.vault(b"vault");
+    /// vault
+    ///     .write_secret(Location::const_generic(b"vault".to_vec(), b"record".to_vec()), b"secret".to_vec())
+    ///     .unwrap();
+    ///
+    /// let stats = client.vault_stats(b"vault").unwrap();
+    /// assert_eq!(stats.records, 1);
+    /// ```
+    pub fn vault_stats<P>(&self, vault_path: P) -> Result<VaultStats, ClientError>
+    where
+        P: AsRef<[u8]>,
+    {
+        let vault_id = derive_vault_id(vault_path);
+        let keystore = self.keystore.read()?;
+        let db = self.db.read()?;
+        let key = keystore.get_key(vault_id).ok_or(ClientError::VaultNotPresent)?;
+        Ok(vault_stats(&key, &db, vault_id))
+    }
+
+    /// Deletes the vault at `vault_path` with all of its records, and removes the key of the vault.
+    ///
+    /// To prevent accidental deletion, the `confirmation` token of the current [`VaultStats`] must
+    /// be provided. If the vault has been modified after the stats were fetched,
+    /// [`ClientError::ConfirmationMismatch`] is returned and nothing is deleted.
+    ///
+    /// # Example
+    /// ```
+    /// use iota_stronghold::{Location, Stronghold};
+    ///
+    /// let stronghold = Stronghold::default();
+    /// let client = stronghold.create_client(b"client").unwrap();
+    /// let vault = client.vault(b"vault");
+    /// vault
+    ///     .write_secret(Location::const_generic(b"vault".to_vec(), b"record".to_vec()), b"secret".to_vec())
+    ///     .unwrap();
+    ///
+    /// let stats = client.vault_stats(b"vault").unwrap();
+    /// client.delete_vault(b"vault", &stats.confirmation).unwrap();
+    /// assert!(!client.vault_exists(b"vault").unwrap());
+    /// ```
+    pub fn delete_vault<P>(&self, vault_path: P, confirmation: &[u8; 32]) -> Result<(), ClientError>
+    where
+        P: AsRef<[u8]>,
+    {
+        let vault_id = derive_vault_id(vault_path);
+        let mut keystore = self.keystore.write()?;
+        let mut db = self.db.write()?;
+
+        let key = keystore.get_key(vault_id).ok_or(ClientError::VaultNotPresent)?;
+        if &vault_stats(&key, &db, vault_id).confirmation != confirmation {
+            return Err(ClientError::ConfirmationMismatch);
+        }
+
+        // removing the vault as a whole can not fail halfway, unlike revoking each record
+        db.vaults.remove(&vault_id);
+        keystore.take_key(vault_id);
+        self.derivation_tree.write()?.remove(&vault_id);
+
+        Ok(())
+    }
+
+    /// Returns the records in the vault at `vault_path` that were derived with the
+    /// [`Slip10Derive`] or [`Slip10DeriveBatch`] procedure, together with the location of the seed
+    /// or parent key they were derived from and the derivation path relative to it. No secrets
+    /// are returned.
+    ///
+    /// A record that was derived from another derived key lists that key as its source, so the
+    /// full path of a record can be reconstructed by following the sources. Records that have
+    /// been revoked or overwritten since are omitted. The derivation tree is persisted with the
+    /// client in the snapshot.
+    ///
+    /// [`Slip10Derive`]: crate::procedures::Slip10Derive
+    /// [`Slip10DeriveBatch`]: crate::procedures::Slip10DeriveBatch
+    ///
+    /// # Example
+    /// ```
+    /// use iota_stronghold::{
+    ///     procedures::{Chain, Slip10Derive, Slip10DeriveInput, Slip10Generate},
+    ///     Location, Stronghold,
+    /// };
+    ///
+    /// let stronghold = Stronghold::default();
+    /// let client = stronghold.create_client(b"client").unwrap();
+    /// let seed = Location::const_generic(b"seeds".to_vec(), b"seed".to_vec());
+    /// client
+    ///     .execute_procedure(Slip10Generate {
+    ///         size_bytes: None,
+    ///         output: seed.clone(),
+    ///     })
+    ///     .unwrap();
+    /// client
+    ///     .execute_procedure(Slip10Derive {
+    ///         chain: Chain::from_u32_hardened(vec![44, 4218, 0]),
+    ///         input: Slip10DeriveInput::Seed(seed),
+    ///         output: Location::const_generic(b"keys".to_vec(), b"account-0".to_vec()),
+    ///     })
+    ///     .unwrap();
+    ///
+    /// let tree = client.derivation_tree(b"keys").unwrap();
+    /// assert_eq!(tree.len(), 1);
+    /// ```
+    pub fn derivation_tree<P>(&self, vault_path: P) -> Result<Vec<DerivedRecord>, ClientError>
+    where
+        P: AsRef<[u8]>,
+    {
+        let vault_id = derive_vault_id(vault_path);
+        let derived = self.derivation_tree.read()?.get(&vault_id).cloned().unwrap_or_default();
+
+        let keystore = self.keystore.read()?;
+        let db = self.db.read()?;
+        let key = match keystore.get_key(vault_id) {
+            Some(key) => key,
+            None => return Ok(Vec::new()),
+        };
+        // revoked records don't have a readable blob id
+        let derived = derived
+            .into_iter()
+            .filter(|entry| {
+                let (vault_id, record_id) = entry.location.resolve();
+                db.get_blob_id(&key, vault_id, record_id).is_ok()
+            })
+            .collect();
+        Ok(derived)
+    }
+
+    /// Updates the derivation tree after records have been written: entries of the `written`
+    /// locations are dropped, and the `derived` records are added. The tree is only metadata, so
+    /// updating it never fails.
+    pub(crate) fn track_derivations(&self, written: &[Location], derived: Vec<DerivedRecord>) {
+        let mut tree = self.derivation_tree.write().unwrap_or_else(|e| e.into_inner());
+        for location in written {
+            let (vault_id, record_id) = location.resolve();
+            if let Some(entries) = tree.get_mut(&vault_id) {
+                entries.retain(|entry| entry.location.resolve().1 != record_id);
+            }
+        }
+        for entry in derived {
+            tree.entry(entry.location.resolve().0).or_default().push(entry);
+        }
+        tree.retain(|_, entries| !entries.is_empty());
+    }
+
+    /// Drops the derivation tree entries of records in `vault_id` that were replaced by a sync.
+    /// The derivation of a synced record is not known to this client.
+    fn untrack_records(&self, vault_id: VaultId, record_ids: &[RecordId]) {
+        let mut tree = self.derivation_tree.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(entries) = tree.get_mut(&vault_id) {
+            entries.retain(|entry| !record_ids.contains(&entry.location.resolve().1));
+            if entries.is_empty() {
+                tree.remove(&vault_id);
+            }
+        }
+    }
+
     /// Synchronize two vaults of the client so that records are copied from `source` to `target`.
     /// If `select_records` is `Some` only the specified records are copied, else a full sync
     /// is performed. If a record already exists at the target, the [`MergePolicy`] applies.
@@ -134,7 +315,9 @@ impl Client {
                 .get_key(vid)
                 .ok_or_else(|| ClientError::Inner(format!("Missing Key for vault {:?}", vid)))?;
             let new_key = key_store.get_or_insert_key(mapped_vid, Key::random())?;
-            db.import_records(&old_key, &new_key, mapped_vid, records)?
+            let record_ids: Vec<RecordId> = records.iter().map(|(rid, _)| *rid).collect();
+            db.import_records(&old_key, &new_key, mapped_vid, records)?;
+            self.untrack_records(mapped_vid, &record_ids);
         }
         Ok(())
     }
@@ -168,7 +351,9 @@ impl Client {
             let mut keystore = self.keystore.write()?;
             let mut db = self.db.write()?;
             let new_key = keystore.get_or_insert_key(mapped_vid, Key::random())?;
-            db.import_records(&old_key, &new_key, mapped_vid, records)?
+            let record_ids: Vec<RecordId> = records.iter().map(|(rid, _)| *rid).collect();
+            db.import_records(&old_key, &new_key, mapped_vid, records)?;
+            self.untrack_records(mapped_vid, &record_ids);
         }
         Ok(())
     }
@@ -184,7 +369,7 @@ impl Client {
     ///
     /// # Example
     pub(crate) fn restore(&mut self, state: ClientState, id: ClientId) -> Result<(), ClientError> {
-        let (keys, db, st) = state;
+        let (keys, db, st, tree) = state;
 
         self.id = id;
 
@@ -192,6 +377,7 @@ impl Client {
         let mut keystore = self.keystore.write()?;
         let mut view = self.db.write()?;
         let mut store = self.store.cache.write()?;
+        let mut derivation_tree = self.derivation_tree.write()?;
 
         let mut new_keystore = KeyStore::<Provider>::default();
         new_keystore
@@ -201,6 +387,7 @@ impl Client {
         *keystore = new_keystore;
         *view = db;
         *store = st;
+        *derivation_tree = tree;
 
         Ok(())
     }
@@ -215,7 +402,105 @@ impl Client {
         view.clear();
         store.clear();
         ks.clear_keys();
+        self.derivation_tree.write()?.clear();
+
+        Ok(())
+    }
+
+    /// Validates a cryptographic [`Procedure`] without executing it.
+    ///
+    /// See [`Client::validate_procedure_chained`] for the performed checks.
+    ///
+    /// # Example
+    /// ```
+    /// use iota_stronghold::{
+    ///     procedures::{KeyType, ProcedureError, PublicKey},
+    ///     Location, Stronghold,
+    /// };
+    ///
+    /// let stronghold = Stronghold::default();
+    /// let client = stronghold.create_client(b"client").unwrap();
+    /// let location = Location::const_generic(b"vault".to_vec(), b"record".to_vec());
+    ///
+    /// let result = client.validate_procedure(PublicKey {
+    ///     ty: KeyType::Ed25519,
+    ///     private_key: location,
+    /// });
+    /// assert!(matches!(result, Err(ProcedureError::MissingRecord(_))));
+    /// ```
+    pub fn validate_procedure<P>(&self, procedure: P) -> Result<(), ProcedureError>
+    where
+        P: Into<StrongholdProcedure>,
+    {
+        self.validate_procedure_chained(&[procedure.into()])
+    }
+
+    /// Validates a chain of cryptographic [`Procedure`]s without executing them. Nothing is
+    /// written to or revoked from the vaults.
+    ///
+    /// Every input location of a procedure must either contain a record, or be the output of a
+    /// previous procedure in the chain. Locations revoked by a previous procedure are treated
+    /// as missing. Existing records must have the length required by the procedure, e.g. an
+    /// X25519 private key must be exactly 32 bytes long. Both existing records and records
+    /// produced earlier in the chain must be of the [`RecordKind`] required by the procedure.
+    ///
+    /// Passing validation does not guarantee that the execution succeeds, since the content of
+    /// a record is not inspected.
+    pub fn validate_procedure_chained(&self, procedures: &[StrongholdProcedure]) -> Result<(), ProcedureError> {
+        // records that are produced or revoked by previous procedures in the chain, together with
+        // the kind of the produced records
+        let mut produced = HashMap::new();
+        let mut revoked = HashSet::new();
+
+        for proc in procedures {
+            let mut input_kinds = Vec::new();
+            for (location, length, kind) in proc.inputs() {
+                let ids = location.resolve();
+                // the length of records produced earlier in the chain is not known before execution
+                let (found, len) = match produced.get(&ids) {
+                    Some(found) => (*found, None),
+                    None => {
+                        let exists = self
+                            .db
+                            .read()
+                            .map_err(|_| ProcedureError::Engine("Acquiring lock failed".to_string().into()))?
+                            .contains_record(ids.0, ids.1);
+                        if !exists || revoked.contains(&ids) {
+                            return Err(ProcedureError::MissingRecord(location));
+                        }
+                        let len = self.get_guards([location.clone()], |[guard]| Ok(guard.borrow().len()))?;
+                        let [found] = self.record_kinds(std::array::from_ref(&location))?;
+                        (found, Some(len))
+                    }
+                };
+                if let Some(expected) = kind {
+                    if !found.satisfies(expected) {
+                        return Err(ProcedureError::WrongRecordKind {
+                            location,
+                            expected,
+                            found,
+                        });
+                    }
+                }
+                if let Some(len) = len {
+                    length
+                        .check(len)
+                        .map_err(|reason| ProcedureError::InvalidInput { location, reason })?;
+                }
+                input_kinds.push(found);
+            }
 
+            if let StrongholdProcedure::RevokeData(RevokeData { location, .. }) = proc {
+                produced.remove(&location.resolve());
+                revoked.insert(location.resolve());
+            }
+            let output_kinds = proc.output_kinds(&input_kinds);
+            for (i, output) in proc.outputs().into_iter().enumerate() {
+                let kind = output_kinds.get(i).copied().unwrap_or(RecordKind::Raw);
+                revoked.remove(&output.resolve());
+                produced.insert(output.resolve(), kind);
+            }
+        }
         Ok(())
     }
 
@@ -241,11 +526,15 @@ impl Client {
     ) -> core::result::Result<Vec<ProcedureOutput>, ProcedureError> {
         let mut out = Vec::new();
         let mut log = Vec::new();
+        let mut derivations = Vec::new();
         // Execute the procedures sequentially.
         for proc in procedures {
-            if let Some(output) = proc.output() {
-                log.push(output);
-            }
+            #[cfg(feature = "fault-injection")]
+            self.faults.delay_procedure();
+
+            let outputs = proc.outputs();
+            log.extend(outputs.clone());
+            derivations.push((outputs, proc.derived_records()));
             let output = match proc.execute(self) {
                 Ok(o) => o,
                 Err(e) => {
@@ -257,6 +546,9 @@ impl Client {
             };
             out.push(output);
         }
+        for (written, derived) in derivations {
+            self.track_derivations(&written, derived);
+        }
         Ok(out)
     }
 }
@@ -274,3 +566,25 @@ impl<'a> SyncClients<'a> for Client {
         Ok(KeyProvider::KeyStore(ks))
     }
 }
+
+/// Computes the [`VaultStats`] of a vault. The records are sorted, so that the confirmation
+/// token does not depend on the order in which records are stored.
+fn vault_stats(key: &Key<Provider>, db: &DbView<Provider>, vault_id: VaultId) -> VaultStats {
+    let mut records = db.list_records(&vault_id);
+    records.sort();
+
+    let mut hasher = Blake2b256::new();
+    hasher.update(vault_id);
+    for record_id in records.iter() {
+        hasher.update(ChainId::from(*record_id));
+        // revoked records don't have a readable blob id
+        if let Ok(blob_id) = db.get_blob_id(key, vault_id, *record_id) {
+            hasher.update(blob_id);
+        }
+    }
+
+    VaultStats {
+        records: records.len(),
+        confirmation: hasher.finalize().into(),
+    }
+}
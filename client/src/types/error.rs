@@ -61,6 +61,15 @@ pub enum ClientError {
 
     #[error("Client with id {0:?} has already been loaded before. Can not be loaded twice.")]
     ClientAlreadyLoaded(ClientId),
+
+    #[error("Size limit exceeded ({0})")]
+    SizeLimitExceeded(#[from] SizeLimitError),
+
+    #[error("Vault does not exist")]
+    VaultNotPresent,
+
+    #[error("Confirmation token does not match the current state of the vault")]
+    ConfirmationMismatch,
 }
 
 impl<T> From<TryLockError<T>> for ClientError {
@@ -113,6 +122,7 @@ impl From<SnapshotError> for ClientError {
             SnapshotError::Engine(inner) => ClientError::Inner(inner),
             SnapshotError::Provider(inner) => ClientError::Inner(inner),
             SnapshotError::Inner(inner) => ClientError::Inner(inner),
+            SnapshotError::SizeLimit(inner) => ClientError::SizeLimitExceeded(inner),
         }
     }
 }
@@ -161,6 +171,22 @@ pub enum SnapshotError {
 
     #[error("Inner error: ({0})")]
     Inner(String),
+
+    #[error("size limit exceeded: {0}")]
+    SizeLimit(#[from] SizeLimitError),
+}
+
+/// A payload exceeded one of the configured [`crate::Limits`].
+#[derive(DeriveError, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SizeLimitError {
+    #[error("record payload of {size} bytes exceeds the maximum of {max} bytes")]
+    RecordSize { size: usize, max: usize },
+
+    #[error("store value of {size} bytes exceeds the maximum of {max} bytes")]
+    StoreValueSize { size: usize, max: usize },
+
+    #[error("snapshot state of {size} bytes exceeds the maximum of {max} bytes")]
+    SnapshotSize { size: usize, max: usize },
 }
 
 pub type RemoteRecordError = String;
@@ -226,6 +252,7 @@ impl From<EngineReadError> for SnapshotError {
                 "Unsupported version: expected {:?}, found {:?}.",
                 expected, found
             )),
+            EngineReadError::SizeLimitExceeded { size, max } => SizeLimitError::SnapshotSize { size, max }.into(),
         }
     }
 }
@@ -0,0 +1,81 @@
+// Copyright 2020-2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::SnapshotError;
+use std::{
+    io,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Hooks to inject faults into a [`crate::Stronghold`] and its [`crate::Client`]s, so that applications
+/// can test their retry and recovery logic against realistic failures. Clones of a [`FaultInjector`]
+/// share the same state.
+///
+/// Only available with the `fault-injection` feature, which must not be enabled in production builds.
+///
+/// # Example
+/// ```
+/// use iota_stronghold::Stronghold;
+/// use std::time::Duration;
+///
+/// let stronghold = Stronghold::default();
+/// let faults = stronghold.faults();
+/// faults.fail_next_snapshot_writes(1);
+/// faults.set_procedure_delay(Some(Duration::from_millis(10)));
+/// faults.reset();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FaultInjector {
+    inner: Arc<Mutex<Faults>>,
+}
+
+#[derive(Debug, Default)]
+struct Faults {
+    // number of upcoming snapshot writes that fail
+    failing_snapshot_writes: usize,
+
+    // delay before each procedure is executed
+    procedure_delay: Option<Duration>,
+}
+
+impl FaultInjector {
+    /// Lets the next `count` snapshot writes fail with an I/O error, before anything is written to disk.
+    pub fn fail_next_snapshot_writes(&self, count: usize) {
+        self.faults().failing_snapshot_writes = count;
+    }
+
+    /// Delays the execution of every procedure by `delay`, to simulate a slow or busy client.
+    /// `None` removes the delay.
+    pub fn set_procedure_delay(&self, delay: Option<Duration>) {
+        self.faults().procedure_delay = delay;
+    }
+
+    /// Removes all injected faults.
+    pub fn reset(&self) {
+        *self.faults() = Faults::default();
+    }
+
+    /// Fails with an injected I/O error, if the current snapshot write should fail.
+    pub(crate) fn snapshot_write(&self) -> Result<(), SnapshotError> {
+        let mut faults = self.faults();
+        if faults.failing_snapshot_writes == 0 {
+            return Ok(());
+        }
+        faults.failing_snapshot_writes -= 1;
+        Err(SnapshotError::Io(io::Error::other("injected fault")))
+    }
+
+    /// Blocks for the configured procedure delay, if any.
+    pub(crate) fn delay_procedure(&self) {
+        let delay = self.faults().procedure_delay;
+        if let Some(delay) = delay {
+            std::thread::sleep(delay);
+        }
+    }
+
+    fn faults(&self) -> std::sync::MutexGuard<'_, Faults> {
+        // the faults are plain counters, so a poisoned lock can safely be recovered
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,85 @@
+// Copyright 2020-2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::SizeLimitError;
+
+/// Upper bounds for the size of data written into a [`crate::Stronghold`]. A limit that has not been
+/// set is not enforced, which is the default for all limits.
+///
+/// # Example
+/// ```
+/// use iota_stronghold::{Limits, Stronghold};
+///
+/// let limits = Limits::default()
+///     .with_max_record_size(1024)
+///     .with_max_store_value_size(4096)
+///     .with_max_snapshot_size(1024 * 1024);
+///
+/// let stronghold = Stronghold::with_limits(limits);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    pub(crate) max_record_size: Option<usize>,
+    pub(crate) max_store_value_size: Option<usize>,
+    pub(crate) max_snapshot_size: Option<usize>,
+}
+
+impl Limits {
+    /// Sets the maximum size in bytes of a secret written into a vault, either directly or as
+    /// the product of a [`crate::procedures::Procedure`].
+    pub fn with_max_record_size(mut self, max: usize) -> Self {
+        self.max_record_size = Some(max);
+        self
+    }
+
+    /// Sets the maximum size in bytes of a single value inserted into a [`crate::Store`].
+    pub fn with_max_store_value_size(mut self, max: usize) -> Self {
+        self.max_store_value_size = Some(max);
+        self
+    }
+
+    /// Sets the maximum size in bytes of the serialized [`crate::Snapshot`] state. The limit
+    /// applies to the state before compression and encryption, when writing as well as when
+    /// reading a snapshot file.
+    pub fn with_max_snapshot_size(mut self, max: usize) -> Self {
+        self.max_snapshot_size = Some(max);
+        self
+    }
+
+    /// Returns the maximum size of a record payload, if set.
+    pub fn max_record_size(&self) -> Option<usize> {
+        self.max_record_size
+    }
+
+    /// Returns the maximum size of a store value, if set.
+    pub fn max_store_value_size(&self) -> Option<usize> {
+        self.max_store_value_size
+    }
+
+    /// Returns the maximum size of the serialized snapshot state, if set.
+    pub fn max_snapshot_size(&self) -> Option<usize> {
+        self.max_snapshot_size
+    }
+
+    pub(crate) fn check_record_size(&self, size: usize) -> Result<(), SizeLimitError> {
+        match self.max_record_size {
+            Some(max) if size > max => Err(SizeLimitError::RecordSize { size, max }),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_store_value_size(&self, size: usize) -> Result<(), SizeLimitError> {
+        match self.max_store_value_size {
+            Some(max) if size > max => Err(SizeLimitError::StoreValueSize { size, max }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Checks `size` against an optional maximum snapshot size.
+pub(crate) fn check_snapshot_size(size: usize, max_size: Option<usize>) -> Result<(), SizeLimitError> {
+    match max_size {
+        Some(max) if size > max => Err(SizeLimitError::SnapshotSize { size, max }),
+        _ => Ok(()),
+    }
+}
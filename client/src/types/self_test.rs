@@ -0,0 +1,329 @@
+// Copyright 2020-2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crypto::{
+    ciphers::{
+        aes_gcm::Aes256Gcm,
+        aes_kw::Aes256Kw,
+        traits::{Aead, Tag},
+    },
+    hashes::{
+        sha::{Sha256, Sha384, Sha512},
+        Digest,
+    },
+    keys::{bip39, pbkdf::PBKDF2_HMAC_SHA256, slip10, x25519},
+    macs::hmac::HMAC_SHA256,
+    signatures::ed25519,
+};
+use engine::{
+    runtime::{check_memory_protection, memories::buffer::Buffer, DEBUG_MSG},
+    snapshot,
+};
+
+/// Outcome of a single check of [`crate::Stronghold::self_test`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestCheck {
+    /// Name of the check, e.g. `"ed25519"`.
+    pub name: &'static str,
+
+    /// Describes why the check failed, if it did.
+    pub result: Result<(), String>,
+}
+
+/// Report of [`crate::Stronghold::self_test`], with the outcome of each check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// Outcome of each check, in the order they were run.
+    pub checks: Vec<SelfTestCheck>,
+}
+
+impl SelfTestReport {
+    /// Returns `true`, if all checks passed.
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.result.is_ok())
+    }
+
+    /// Returns the checks that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &SelfTestCheck> {
+        self.checks.iter().filter(|check| check.result.is_err())
+    }
+}
+
+type Check = fn() -> Result<(), String>;
+
+const CHECKS: [(&str, Check); 14] = [
+    ("sha256", sha256),
+    ("sha384", sha384),
+    ("sha512", sha512),
+    ("hmac_sha256", hmac_sha256),
+    ("hkdf_sha256", hkdf_sha256),
+    ("pbkdf2_hmac_sha256", pbkdf2_hmac_sha256),
+    ("bip39", bip39),
+    ("slip10", slip10),
+    ("ed25519", ed25519),
+    ("x25519", x25519),
+    ("aes_256_gcm", aes_256_gcm),
+    ("aes_256_kw", aes_256_kw),
+    ("snapshot_cipher", snapshot_cipher),
+    ("memory_protection", memory_protection),
+];
+
+/// Runs all checks. Checks report failures as errors, and must not panic.
+pub(crate) fn run() -> SelfTestReport {
+    let checks = CHECKS
+        .iter()
+        .map(|&(name, check)| SelfTestCheck { name, result: check() })
+        .collect();
+    SelfTestReport { checks }
+}
+
+// FIPS 180-2, appendix B.1
+fn sha256() -> Result<(), String> {
+    expect(
+        "digest",
+        &Sha256::digest(b"abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+    )
+}
+
+// FIPS 180-2, appendix D.1
+fn sha384() -> Result<(), String> {
+    expect(
+        "digest",
+        &Sha384::digest(b"abc"),
+        "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7",
+    )
+}
+
+// FIPS 180-2, appendix C.1
+fn sha512() -> Result<(), String> {
+    expect(
+        "digest",
+        &Sha512::digest(b"abc"),
+        "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+         2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+    )
+}
+
+// RFC 4231, test case 2
+fn hmac_sha256() -> Result<(), String> {
+    let mut mac = [0; 32];
+    HMAC_SHA256(b"what do ya want for nothing?", b"Jefe", &mut mac);
+    expect(
+        "mac",
+        &mac,
+        "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+    )
+}
+
+// RFC 5869, test case 1
+fn hkdf_sha256() -> Result<(), String> {
+    let ikm = [0x0b; 22];
+    let salt = unhex("000102030405060708090a0b0c")?;
+    let info = unhex("f0f1f2f3f4f5f6f7f8f9")?;
+    let mut okm = [0; 42];
+    hkdf::Hkdf::<Sha256>::new(Some(&salt), &ikm)
+        .expand(&info, &mut okm)
+        .map_err(|e| e.to_string())?;
+    expect(
+        "output key material",
+        &okm,
+        "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865",
+    )
+}
+
+// RFC 7914, section 11, first test vector
+fn pbkdf2_hmac_sha256() -> Result<(), String> {
+    let mut key = [0; 64];
+    PBKDF2_HMAC_SHA256(b"passwd", b"salt", 1, &mut key).map_err(|e| e.to_string())?;
+    expect(
+        "derived key",
+        &key,
+        "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+         49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783",
+    )
+}
+
+// BIP39 reference vectors, first English vector (passphrase "TREZOR")
+fn bip39() -> Result<(), String> {
+    let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+    let mut seed = [0; 64];
+    bip39::mnemonic_to_seed(mnemonic, "TREZOR", &mut seed);
+    expect(
+        "seed",
+        &seed,
+        "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e5349553\
+         1f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+    )
+}
+
+// SLIP-0010, test vector 1 for ed25519, chain m/0H/1H
+fn slip10() -> Result<(), String> {
+    let seed = slip10::Seed::from_bytes(&unhex("000102030405060708090a0b0c0d0e0f")?);
+    let key = seed
+        .derive(slip10::Curve::Ed25519, &slip10::Chain::from_u32_hardened([0, 1]))
+        .map_err(|e| e.to_string())?;
+    expect(
+        "chain code",
+        &key.chain_code(),
+        "a320425f77d1b5c2505a6b1b27382b37368ee640e3557c315416801243552f14",
+    )?;
+    expect(
+        "private key",
+        &key.secret_key().to_bytes(),
+        "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2",
+    )
+}
+
+// RFC 8032, section 7.1, test 1
+fn ed25519() -> Result<(), String> {
+    let sk = ed25519::SecretKey::from_bytes(unhex_array(
+        "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+    )?);
+    let pk = sk.public_key();
+    expect(
+        "public key",
+        &pk.to_bytes(),
+        "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+    )?;
+
+    let sig = sk.sign(b"");
+    expect(
+        "signature",
+        &sig.to_bytes(),
+        "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555\
+         fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+    )?;
+    if !pk.verify(&sig, b"") {
+        return Err("signature verification failed".to_string());
+    }
+    Ok(())
+}
+
+// RFC 7748, section 6.1
+fn x25519() -> Result<(), String> {
+    let sk = x25519::SecretKey::from_bytes(unhex_array(
+        "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a",
+    )?);
+    expect(
+        "public key",
+        &sk.public_key().to_bytes(),
+        "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a",
+    )?;
+
+    let pk = x25519::PublicKey::from_bytes(unhex_array(
+        "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f",
+    )?);
+    expect(
+        "shared secret",
+        &sk.diffie_hellman(&pk).to_bytes(),
+        "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742",
+    )
+}
+
+// The Galois/Counter Mode of Operation (GCM), test case 14
+fn aes_256_gcm() -> Result<(), String> {
+    let key = [0; Aes256Gcm::KEY_LENGTH];
+    let nonce = [0; Aes256Gcm::NONCE_LENGTH];
+    let plaintext = [0; 16];
+
+    let mut ciphertext = [0; 16];
+    let mut tag = Tag::<Aes256Gcm>::default();
+    Aes256Gcm::try_encrypt(&key, &nonce, &[], &plaintext, &mut ciphertext, &mut tag).map_err(|e| e.to_string())?;
+    expect("ciphertext", &ciphertext, "cea7403d4d606b6e074ec5d3baf39d18")?;
+    expect("tag", &tag, "d0d1c8a799996bf0265b98b5d48ab919")?;
+
+    let mut decrypted = [0xff; 16];
+    Aes256Gcm::try_decrypt(&key, &nonce, &[], &mut decrypted, &ciphertext, &tag).map_err(|e| e.to_string())?;
+    expect("plaintext", &decrypted, "00000000000000000000000000000000")
+}
+
+// RFC 3394, section 4.6
+fn aes_256_kw() -> Result<(), String> {
+    let kek = unhex("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f")?;
+    let key = unhex("00112233445566778899aabbccddeeff000102030405060708090a0b0c0d0e0f")?;
+    let wrap = Aes256Kw::new(&kek);
+
+    let mut wrapped = [0; 32 + Aes256Kw::BLOCK];
+    wrap.wrap_key(&key, &mut wrapped).map_err(|e| e.to_string())?;
+    expect(
+        "wrapped key",
+        &wrapped,
+        "28c9f404c4b810f4cbccb35cfb87f8263f5786e2d80ed326cbc7f0e71a99f43bfb988b9b7a02dd21",
+    )?;
+
+    let mut unwrapped = [0; 32];
+    wrap.unwrap_key(&wrapped, &mut unwrapped).map_err(|e| e.to_string())?;
+    if unwrapped[..] != key[..] {
+        return Err("unwrapped key does not match the known answer".to_string());
+    }
+    Ok(())
+}
+
+// Third test vector of `test_vectors` in engine/src/snapshot/logic.rs (empty data and associated
+// data), followed by a round trip of the snapshot cipher
+fn snapshot_cipher() -> Result<(), String> {
+    let key: snapshot::Key = unhex_array("cd250a0b070632dc521cfe35805b2846763a4c698d61d85d3b55f115b9a769da")?;
+    let file = unhex(
+        "50415254490200665c393f383466881adbbc788ff49389f2268f9b6d43084e9f\
+         f8bfc945b09501ac469b5ad0e666eada7c7566a295574a",
+    )?;
+
+    let (magic, rest) = file.split_at(snapshot::MAGIC.len());
+    let (version, mut body) = rest.split_at(snapshot::VERSION.len());
+    if magic != snapshot::MAGIC || version != snapshot::VERSION {
+        return Err("unexpected snapshot header".to_string());
+    }
+    let plain = snapshot::read(&mut body, &key, &[]).map_err(|e| e.to_string())?;
+    expect("plaintext", &plain, "")?;
+
+    let data = b"stronghold self-test";
+    let mut written = Vec::new();
+    snapshot::write(data, &mut written, &key, b"associated data").map_err(|e| e.to_string())?;
+    let plain = snapshot::read(&mut written.as_slice(), &key, b"associated data").map_err(|e| e.to_string())?;
+    if plain != data {
+        return Err("snapshot round trip returned different data".to_string());
+    }
+    Ok(())
+}
+
+// Guarded memory can be locked and protected, and hides its content
+fn memory_protection() -> Result<(), String> {
+    check_memory_protection().map_err(|e| e.to_string())?;
+
+    let data = b"stronghold self-test";
+    let buffer = Buffer::alloc(data, data.len());
+    if *buffer.borrow() != data[..] {
+        return Err("guarded buffer returned different data".to_string());
+    }
+    if format!("{:?}", buffer) != DEBUG_MSG {
+        return Err("guarded buffer exposes its content".to_string());
+    }
+    Ok(())
+}
+
+fn expect(what: &str, found: &[u8], expected: &str) -> Result<(), String> {
+    if unhex(expected)? != found {
+        return Err(format!("{} does not match the known answer", what));
+    }
+    Ok(())
+}
+
+fn unhex_array<const N: usize>(hex: &str) -> Result<[u8; N], String> {
+    unhex(hex)?
+        .try_into()
+        .map_err(|_| format!("expected {} bytes of hex", N))
+}
+
+fn unhex(hex: &str) -> Result<Vec<u8>, String> {
+    hex.as_bytes()
+        .chunks(2)
+        .map(|digits| match digits {
+            [_, _] => std::str::from_utf8(digits)
+                .ok()
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .ok_or_else(|| "invalid hex digits".to_string()),
+            _ => Err("odd number of hex digits".to_string()),
+        })
+        .collect()
+}
@@ -8,11 +8,15 @@
 
 use crypto::keys::x25519;
 use engine::{
-    snapshot::{self, read, read_from as read_from_file, write, write_to as write_to_file, Key},
+    snapshot::{self, read, read_from as read_from_file, read_from_with_limit, write, write_to as write_to_file, Key},
     store::Cache,
     vault::{view::Record, BlobId, BoxProvider, ClientId, DbView, Key as PKey, RecordHint, RecordId, VaultId},
 };
-use serde::{Deserialize, Serialize};
+use serde::{
+    de::{SeqAccess, Visitor},
+    ser::SerializeTuple,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
 use std::{
     collections::HashMap,
     convert::Infallible,
@@ -26,15 +30,25 @@ use zeroize::Zeroize;
 use crate::{
     procedures::{DeriveSecret, X25519DiffieHellman},
     sync::{self, KeyProvider, SnapshotHierarchy, SyncClients, SyncClientsConfig, SyncSnapshots, SyncSnapshotsConfig},
-    ClientError, KeyStore, Location, Provider, SnapshotError,
+    ClientError, DerivationTree, KeyStore, Location, Provider, SnapshotError,
 };
 
+use super::limits::check_snapshot_size;
+
 type EncryptedClientState = (Vec<u8>, Cache<Vec<u8>, Vec<u8>>);
 
 pub type ClientState = (
     HashMap<VaultId, PKey<Provider>>,
     DbView<Provider>,
     Cache<Vec<u8>, Vec<u8>>,
+    DerivationTree,
+);
+
+// The state of a client in a snapshot file, without its derivation tree.
+type PersistedClientState = (
+    HashMap<VaultId, PKey<Provider>>,
+    DbView<Provider>,
+    Cache<Vec<u8>, Vec<u8>>,
 );
 
 impl<'a> SyncClients<'a> for ClientState {
@@ -61,9 +75,72 @@ pub struct Snapshot {
 }
 
 /// Data structure that is written to the snapshot.
-#[derive(Deserialize, Serialize, Default)]
+#[derive(Default)]
 pub struct SnapshotState(pub(crate) HashMap<ClientId, ClientState>);
 
+// The derivation trees are written after the client states, so that readers which only know the
+// client states can still read the snapshot, and snapshots without trees remain readable.
+impl Serialize for SnapshotState {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut states = HashMap::new();
+        let mut trees = HashMap::new();
+        for (client_id, (keys, db, store, tree)) in self.0.iter() {
+            states.insert(client_id, (keys, db, store));
+            if !tree.is_empty() {
+                trees.insert(client_id, tree);
+            }
+        }
+        let mut tuple = serializer.serialize_tuple(2)?;
+        tuple.serialize_element(&states)?;
+        tuple.serialize_element(&trees)?;
+        tuple.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for SnapshotState {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct SnapshotStateVisitor;
+
+        impl<'de> Visitor<'de> for SnapshotStateVisitor {
+            type Value = SnapshotState;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("client states followed by their derivation trees")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let states: HashMap<ClientId, PersistedClientState> = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                // snapshots written before derivation trees were tracked end after the client
+                // states. The tree only describes how records were derived, an unreadable tree is
+                // dropped.
+                let mut trees: HashMap<ClientId, DerivationTree> =
+                    seq.next_element().ok().flatten().unwrap_or_default();
+                let states = states
+                    .into_iter()
+                    .map(|(client_id, (keys, db, store))| {
+                        let tree = trees.remove(&client_id).unwrap_or_default();
+                        (client_id, (keys, db, store, tree))
+                    })
+                    .collect();
+                Ok(SnapshotState(states))
+            }
+        }
+
+        deserializer.deserialize_tuple(2, SnapshotStateVisitor)
+    }
+}
+
 /// A handle for snapshot file locations.
 ///
 /// # Examples
@@ -171,11 +248,18 @@ impl Snapshot {
                 k.borrow().deref().try_into().ok().map(|k| (state, k))
             }) {
             Some(t) => t,
-            None => return Ok((HashMap::default(), DbView::default(), Cache::default())),
+            None => {
+                return Ok((
+                    HashMap::default(),
+                    DbView::default(),
+                    Cache::default(),
+                    DerivationTree::default(),
+                ))
+            }
         };
         let decrypted = read(&mut encrypted.as_slice(), &key, &[])?;
-        let (keys, db) = bincode::deserialize(&decrypted)?;
-        Ok((keys, db, store.clone()))
+        let (keys, db, tree) = bincode::deserialize(&decrypted)?;
+        Ok((keys, db, store.clone(), tree))
     }
 
     /// Purges a [`crate::Client`] from the [`SnapshotState`]. The next write to the Snapshot file
@@ -202,7 +286,22 @@ impl Snapshot {
         key: Key,
         write_key: Option<(VaultId, RecordId)>,
     ) -> Result<Self, SnapshotError> {
-        let data = read_from_file(snapshot_path.as_path(), &key, &[])?;
+        Self::read_from_snapshot_with_limit(snapshot_path, key, write_key, None)
+    }
+
+    /// Reads state from the specified named snapshot or the specified path, and fails
+    /// if the decrypted state is larger than `max_size`. Files that are too large are rejected
+    /// before they are decrypted.
+    pub(crate) fn read_from_snapshot_with_limit(
+        snapshot_path: &SnapshotPath,
+        key: Key,
+        write_key: Option<(VaultId, RecordId)>,
+        max_size: Option<usize>,
+    ) -> Result<Self, SnapshotError> {
+        let data = match max_size {
+            Some(max_size) => read_from_with_limit(snapshot_path.as_path(), &key, &[], max_size)?,
+            None => read_from_file(snapshot_path.as_path(), &key, &[])?,
+        };
 
         let state = bincode::deserialize(&data)?;
         Snapshot::from_state(state, key, write_key)
@@ -211,8 +310,20 @@ impl Snapshot {
     /// Writes state to the specified named snapshot or the specified path
     /// TODO: Add associated data.
     pub fn write_to_snapshot(&self, snapshot_path: &SnapshotPath, use_key: UseKey) -> Result<(), SnapshotError> {
+        self.write_to_snapshot_with_limit(snapshot_path, use_key, None)
+    }
+
+    /// Writes state to the specified named snapshot or the specified path, and fails
+    /// without touching the file if the serialized state is larger than `max_size`.
+    pub(crate) fn write_to_snapshot_with_limit(
+        &self,
+        snapshot_path: &SnapshotPath,
+        use_key: UseKey,
+        max_size: Option<usize>,
+    ) -> Result<(), SnapshotError> {
         let state = self.get_snapshot_state()?;
         let data = bincode::serialize(&state)?;
+        check_snapshot_size(data.len(), max_size)?;
 
         let key = match use_key {
             UseKey::Key(k) => k,
@@ -233,16 +344,8 @@ impl Snapshot {
     }
 
     /// Adds data to the snapshot state hashmap.
-    pub fn add_data(
-        &mut self,
-        id: ClientId,
-        (keys, db, store): (
-            HashMap<VaultId, PKey<Provider>>,
-            DbView<Provider>,
-            Cache<Vec<u8>, Vec<u8>>,
-        ),
-    ) -> Result<(), SnapshotError> {
-        let bytes = bincode::serialize(&(keys, db))?;
+    pub fn add_data(&mut self, id: ClientId, (keys, db, store, tree): ClientState) -> Result<(), SnapshotError> {
+        let bytes = bincode::serialize(&(keys, db, tree))?;
         let vault_id = VaultId(id.0);
         let key: snapshot::Key = random::random();
         let mut buffer = Vec::new();
@@ -9,7 +9,7 @@ use std::{
     time::Duration,
 };
 
-use crate::ClientError;
+use crate::{ClientError, Limits};
 use engine::store::Cache;
 use serde::{de::DeserializeSeed, Deserialize, Serialize};
 
@@ -36,10 +36,20 @@ use serde::{de::DeserializeSeed, Deserialize, Serialize};
 #[derive(Clone, Default)]
 pub struct Store {
     pub(crate) cache: Arc<RwLock<Cache<Vec<u8>, Vec<u8>>>>,
+    pub(crate) limits: Limits,
 }
 
 impl Store {
-    /// Inserts a `value` into the store with `key`
+    /// Creates an empty [`Store`] that enforces the given [`Limits`] on inserted values
+    pub(crate) fn with_limits(limits: Limits) -> Self {
+        Self {
+            cache: Default::default(),
+            limits,
+        }
+    }
+
+    /// Inserts a `value` into the store with `key`. Fails with [`ClientError::SizeLimitExceeded`],
+    /// if the value is larger than the configured maximum store value size.
     ///
     /// # Example
     /// ```
@@ -56,6 +66,7 @@ impl Store {
         value: Vec<u8>,
         lifetime: Option<Duration>,
     ) -> Result<Option<Vec<u8>>, ClientError> {
+        self.limits.check_store_value_size(value.len())?;
         let mut guard = self.cache.write()?;
         Ok(guard.insert(key.to_vec(), value, lifetime))
     }
@@ -120,6 +131,49 @@ impl Store {
         Ok(guard.get(&key.to_vec()).is_some())
     }
 
+    /// Returns the remaining lifetime of the value with `key`. The result is [`None`], if the key does
+    /// not exist, and `Some(None)`, if the value never expires.
+    ///
+    /// # Example
+    /// ```
+    /// use iota_stronghold::Store;
+    /// use std::time::Duration;
+    ///
+    /// let store = Store::default();
+    /// let key = b"some key".to_vec();
+    /// store
+    ///     .insert(key.clone(), b"some data".to_vec(), Some(Duration::from_secs(60)))
+    ///     .unwrap();
+    /// let lifetime = store.lifetime(&key).unwrap().flatten().unwrap();
+    /// assert!(lifetime <= Duration::from_secs(60));
+    /// ```
+    pub fn lifetime(&self, key: &[u8]) -> Result<Option<Option<Duration>>, ClientError> {
+        let guard = self.cache.read()?;
+        Ok(guard.lifetime(&key.to_vec()))
+    }
+
+    /// Replaces the lifetime of the value with `key`, without rewriting the value. The new `lifetime`
+    /// starts now, and [`None`] lets the value never expire. Returns `false`, if the key does not exist.
+    ///
+    /// # Example
+    /// ```
+    /// use iota_stronghold::Store;
+    /// use std::time::Duration;
+    ///
+    /// let store = Store::default();
+    /// let key = b"some key".to_vec();
+    /// store
+    ///     .insert(key.clone(), b"some data".to_vec(), Some(Duration::from_secs(1)))
+    ///     .unwrap();
+    /// assert!(store.touch(&key, Some(Duration::from_secs(3600))).unwrap());
+    /// assert!(store.touch(&key, None).unwrap());
+    /// assert_eq!(store.lifetime(&key).unwrap(), Some(None));
+    /// ```
+    pub fn touch(&self, key: &[u8], lifetime: Option<Duration>) -> Result<bool, ClientError> {
+        let mut guard = self.cache.write()?;
+        Ok(guard.set_lifetime(&key.to_vec(), lifetime))
+    }
+
     /// Reloads the [`Store`] with a given [`Cache`]
     ///
     /// # Examples
@@ -185,6 +239,7 @@ impl<'a> Deserialize<'a> for Store {
         let cache = Cache::deserialize(deserializer)?;
         Ok(Store {
             cache: Arc::new(RwLock::new(cache)),
+            limits: Limits::default(),
         })
     }
 }
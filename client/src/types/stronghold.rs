@@ -1,17 +1,24 @@
 // Copyright 2020-2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
+use super::self_test;
 use crate::{
-    procedures::Runner,
+    procedures::{ProcedureError, ProcedureOutput, Runner, StrongholdProcedure},
     sync::{SnapshotHierarchy, SyncSnapshots, SyncSnapshotsConfig},
-    Client, ClientError, ClientState, KeyProvider, LoadFromPath, Location, RemoteMergeError, RemoteVaultError,
-    Snapshot, SnapshotPath, Store, UseKey,
+    Capabilities, Client, ClientError, ClientState, KeyProvider, Limits, LoadFromPath, Location, RemoteMergeError,
+    RemoteVaultError, SelfTestReport, Snapshot, SnapshotError, SnapshotPath, Store, UseKey,
 };
+
+#[cfg(feature = "fault-injection")]
+use crate::FaultInjector;
 use crypto::keys::x25519;
 use engine::vault::ClientId;
 use std::{
     collections::{hash_map::Entry, HashMap},
     ops::Deref,
-    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    sync::{
+        mpsc::{self, Receiver},
+        Arc, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    },
 };
 use stronghold_utils::GuardDebug;
 use zeroize::Zeroize;
@@ -30,6 +37,7 @@ macro_rules! write_with_clientid {
         let mut keystore_guard = client.keystore.write()?;
         let view = client.db.read()?;
         let store = client.store.cache.read()?;
+        let tree = client.derivation_tree.read()?;
 
         // we need some compatibility code here. Keyprovider stores encrypted vec
         // by snapshot requires a mapping to Key<Provider>
@@ -39,7 +47,10 @@ macro_rules! write_with_clientid {
         // This might be critical, as keystore gets copied into Boxed types, but still safe
         // we also use cloned data, which might not be ideal.
         ($snapshot)
-            .add_data(($client_id), (keystore, (*view).clone(), (*store).clone()))
+            .add_data(
+                ($client_id),
+                (keystore, (*view).clone(), (*store).clone(), (*tree).clone()),
+            )
             .map_err(|e| ClientError::Inner(e.to_string()))?;
     }};
 }
@@ -49,7 +60,7 @@ macro_rules! write_with_clientid {
 /// ending at the end of a function
 /// # Example
 macro_rules! load_snapshot {
-    ($snapshot:expr, $snapshot_path:expr, $keyprovider:expr) => {{
+    ($snapshot:expr, $snapshot_path:expr, $keyprovider:expr, $limits:expr) => {{
         {
             if !($snapshot_path).exists() {
                 let path = ($snapshot_path)
@@ -66,13 +77,28 @@ macro_rules! load_snapshot {
                 .map_err(|e| ClientError::Inner(format!("{:?}", e)))?;
             let buffer_ref = buffer.borrow().deref().try_into().unwrap();
 
-            *($snapshot) = Snapshot::read_from_snapshot(($snapshot_path), buffer_ref, None)
-                .map_err(|e| ClientError::Inner(e.to_string()))?;
+            *($snapshot) = Snapshot::read_from_snapshot_with_limit(
+                ($snapshot_path),
+                buffer_ref,
+                None,
+                ($limits).max_snapshot_size(),
+            )
+            .map_err(ClientError::from)?;
             // END CRITICAL SECTION
         }
     }};
 }
 
+/// Progress of [`Stronghold::preload_clients`] for a single client path.
+#[derive(Debug)]
+pub enum PreloadEvent {
+    /// The client has been pre-loaded and is returned by the next [`Stronghold::load_client`].
+    Ready(Vec<u8>),
+
+    /// Pre-loading the client failed.
+    Failed(Vec<u8>, ClientError),
+}
+
 /// The Stronghold is a secure storage for sensitive data. Secrets that are stored inside
 /// a Stronghold can never be read, but only be accessed via cryptographic procedures. Data inside
 /// a Stronghold is heavily protected by the `Runtime` by either being encrypted at rest, having
@@ -87,19 +113,109 @@ pub struct Stronghold {
     /// A map of [`ClientId`] to [`Client`]s
     clients: Arc<RwLock<HashMap<ClientId, Client>>>,
 
+    /// [`Client`]s pre-loaded from the [`Snapshot`] by [`Stronghold::preload_clients`], that have not
+    /// been loaded yet
+    warm_clients: Arc<RwLock<HashMap<ClientId, Client>>>,
+
+    /// Designated snapshot files of [`Client`]s, which are not persisted in the shared [`Snapshot`]
+    client_snapshots: Arc<RwLock<HashMap<ClientId, (SnapshotPath, KeyProvider)>>>,
+
     // A per Stronghold session store
     store: Store,
 
     /// Optional key location for writing to [`Snapshot`]
     key_location: Arc<RwLock<Option<Location>>>,
+
+    /// Size limits enforced on records, store values and snapshots
+    limits: Limits,
+
+    /// Faults injected into this [`Stronghold`] and its [`Client`]s
+    #[cfg(feature = "fault-injection")]
+    faults: FaultInjector,
 }
 
 impl Stronghold {
-    /// Drop all references
+    /// Creates a new [`Stronghold`] that enforces the given [`Limits`] on all of its [`Client`]s,
+    /// [`Store`]s and [`Snapshot`] files.
+    ///
+    /// # Example
+    /// ```
+    /// use iota_stronghold::{Limits, Stronghold};
+    ///
+    /// let stronghold = Stronghold::with_limits(Limits::default().with_max_record_size(64));
+    /// assert_eq!(stronghold.limits().max_record_size(), Some(64));
+    /// ```
+    pub fn with_limits(limits: Limits) -> Self {
+        Self {
+            store: Store::with_limits(limits),
+            limits,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the [`Limits`] of this [`Stronghold`]
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// Drop all references. Configured [`Limits`] are kept.
     ///
     /// # Example
     pub fn reset(self) -> Self {
-        Self::default()
+        Self {
+            #[cfg(feature = "fault-injection")]
+            faults: self.faults.clone(),
+            ..Self::with_limits(self.limits)
+        }
+    }
+
+    /// Returns the [`FaultInjector`] of this [`Stronghold`], which controls the faults injected into
+    /// it and all of its [`Client`]s.
+    #[cfg(feature = "fault-injection")]
+    pub fn faults(&self) -> FaultInjector {
+        self.faults.clone()
+    }
+
+    /// Creates an empty [`Client`] with `client_id` that inherits the limits of this [`Stronghold`]
+    fn new_client(&self, client_id: ClientId) -> Client {
+        Client {
+            id: client_id,
+            store: Store::with_limits(self.limits),
+            limits: self.limits,
+            #[cfg(feature = "fault-injection")]
+            faults: self.faults.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Runs a power-on self-test: known-answer tests of the cryptographic primitives used by
+    /// procedures and of the snapshot cipher, and a check that the runtime memory protections
+    /// are active. The self-test does not access any client or snapshot data.
+    ///
+    /// # Example
+    /// ```
+    /// use iota_stronghold::Stronghold;
+    ///
+    /// let report = Stronghold::self_test();
+    /// assert!(report.passed(), "failed checks: {:?}", report.failures().collect::<Vec<_>>());
+    /// ```
+    pub fn self_test() -> SelfTestReport {
+        self_test::run()
+    }
+
+    /// Returns a manifest of the [`Capabilities`] of this build: the enabled features, the supported
+    /// procedures, ciphers and key types, the snapshot format version and the runtime protections of
+    /// the current platform.
+    ///
+    /// # Example
+    /// ```
+    /// use iota_stronghold::Stronghold;
+    ///
+    /// let capabilities = Stronghold::capabilities();
+    /// assert!(capabilities.procedures.iter().any(|name| name == "Ed25519Sign"));
+    /// ```
+    pub fn capabilities() -> Capabilities {
+        Capabilities::current()
     }
 
     /// Returns an atomic reference to the [`Store`]
@@ -111,7 +227,8 @@ impl Stronghold {
     ///
     /// The [`Snapshot`] is secured in memory and may be used to load further
     /// clients with [`Stronghold::load_client`].
-    /// Load a [`Client`] at `client_path` from the snapshot.
+    /// Load a [`Client`] at `client_path` from the snapshot, or from its designated snapshot file,
+    /// if one has been set with [`Self::set_client_snapshot`].
     /// The function returns an error if the client path is not in the snapshot
     /// or a client with the same id has already been loaded before.
     pub fn load_client_from_snapshot<P>(
@@ -123,22 +240,27 @@ impl Stronghold {
     where
         P: AsRef<[u8]>,
     {
-        let mut client = Client::default();
         let client_id = ClientId::load_from_path(client_path.as_ref(), client_path.as_ref());
+        let mut client = self.new_client(client_id);
 
         let mut snapshot = self.snapshot.write()?;
         let mut clients = self.clients.write()?;
 
-        load_snapshot!(snapshot, snapshot_path, keyprovider);
+        load_snapshot!(snapshot, snapshot_path, keyprovider, self.limits);
+        self.warm_clients.write()?.clear();
 
         // If a client has already been loaded returns an error
         if clients.contains_key(&client_id) {
             return Err(ClientError::ClientAlreadyLoaded(client_id));
         }
 
-        let client_state: ClientState = snapshot
-            .get_state(client_id)
-            .map_err(|e| ClientError::Inner(e.to_string()))?;
+        let client_state = match self.read_client_state(&snapshot, client_id) {
+            // clients that are not present in the snapshot are loaded with an empty state
+            Err(ClientError::ClientDataNotPresent) => snapshot
+                .get_state(client_id)
+                .map_err(|e| ClientError::Inner(e.to_string()))?,
+            state => state?,
+        };
 
         // Load the client state
         client.restore(client_state, client_id)?;
@@ -149,7 +271,9 @@ impl Stronghold {
         Ok(client)
     }
 
-    /// Loads a client from [`Snapshot`] data
+    /// Loads a client from [`Snapshot`] data, or from its designated snapshot file, if one has been set
+    /// with [`Self::set_client_snapshot`]. A client that has been pre-loaded with
+    /// [`Self::preload_clients`] is returned without decrypting the [`Snapshot`] data again.
     ///
     /// The function returns an error if the client path is not in the snapshot
     /// or a client with the same id has already been loaded before.
@@ -158,7 +282,7 @@ impl Stronghold {
         P: AsRef<[u8]>,
     {
         let client_id = ClientId::load_from_path(client_path.as_ref(), client_path.as_ref());
-        let mut client = Client::default();
+        let mut client = self.new_client(client_id);
 
         let snapshot = self.snapshot.read()?;
         let mut clients = self.clients.write()?;
@@ -168,13 +292,12 @@ impl Stronghold {
             return Err(ClientError::ClientAlreadyLoaded(client_id));
         }
 
-        if !snapshot.has_data(client_id) {
-            return Err(ClientError::ClientDataNotPresent);
+        if let Some(client) = self.warm_clients.write()?.remove(&client_id) {
+            clients.insert(client_id, client.clone());
+            return Ok(client);
         }
 
-        let client_state: ClientState = snapshot
-            .get_state(client_id)
-            .map_err(|e| ClientError::Inner(e.to_string()))?;
+        let client_state = self.read_client_state(&snapshot, client_id)?;
 
         // Load the client state
         client.restore(client_state, client_id)?;
@@ -185,6 +308,79 @@ impl Stronghold {
         Ok(client)
     }
 
+    /// Pre-loads the [`Client`]s at `client_paths` from the [`Snapshot`] in a background thread, so that
+    /// a subsequent [`Self::load_client`] does not have to decrypt and restore the client state first.
+    /// A [`Snapshot`] must have been loaded with [`Self::load_snapshot`] before.
+    ///
+    /// The returned [`Receiver`] yields one [`PreloadEvent`] per client path, in the given order, as
+    /// soon as the client is warm or pre-loading it failed. Dropping the [`Receiver`] does not stop
+    /// pre-loading. Pre-loaded clients are discarded, if another [`Snapshot`] is loaded.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use iota_stronghold::{KeyProvider, PreloadEvent, SnapshotPath, Stronghold};
+    ///
+    /// let stronghold = Stronghold::default();
+    /// let key_provider = KeyProvider::try_from(vec![0u8; 32]).unwrap();
+    /// stronghold
+    ///     .load_snapshot(&key_provider, &SnapshotPath::named("snapshot-file"))
+    ///     .unwrap();
+    ///
+    /// let events = stronghold.preload_clients(vec![b"client".to_vec()]);
+    /// if let Ok(PreloadEvent::Ready(client_path)) = events.recv() {
+    ///     let client = stronghold.load_client(client_path).unwrap();
+    /// }
+    /// ```
+    pub fn preload_clients<P>(&self, client_paths: Vec<P>) -> Receiver<PreloadEvent>
+    where
+        P: AsRef<[u8]> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+        let stronghold = self.clone();
+
+        std::thread::spawn(move || {
+            for client_path in client_paths {
+                let client_path = client_path.as_ref().to_vec();
+                let event = match stronghold.preload_client(&client_path) {
+                    Ok(()) => PreloadEvent::Ready(client_path),
+                    Err(e) => PreloadEvent::Failed(client_path, e),
+                };
+                // keep pre-loading, even if nobody is listening anymore
+                let _ = tx.send(event);
+            }
+        });
+
+        rx
+    }
+
+    /// Restores the [`Client`] at `client_path` from the [`Snapshot`] into the warm clients
+    fn preload_client(&self, client_path: &[u8]) -> Result<(), ClientError> {
+        let client_id = ClientId::load_from_path(client_path, client_path);
+        let mut client = self.new_client(client_id);
+
+        // the snapshot stays locked until the client is warm, so that it can not be replaced in between
+        let snapshot = self.snapshot.read()?;
+
+        let client_state = self.read_client_state(&snapshot, client_id)?;
+        client.restore(client_state, client_id)?;
+
+        let clients = self.clients.read()?;
+        if clients.contains_key(&client_id) {
+            return Err(ClientError::ClientAlreadyLoaded(client_id));
+        }
+        self.warm_clients.write()?.insert(client_id, client);
+
+        Ok(())
+    }
+
+    /// Drops the pre-loaded state of the [`Client`] with `client_id`. Must be called whenever the
+    /// client is created, written or its source in the [`Snapshot`] changes, so that
+    /// [`Self::load_client`] never hands out stale state.
+    fn invalidate_warm_client(&self, client_id: &ClientId) -> Result<(), ClientError> {
+        self.warm_clients.write()?.remove(client_id);
+        Ok(())
+    }
+
     /// Returns an in session client, not being persisted in a [`Snapshot`]
     ///
     /// # Example
@@ -200,6 +396,161 @@ impl Stronghold {
             .ok_or(ClientError::ClientDataNotPresent)
     }
 
+    /// Designates a snapshot file for the [`Client`] at `client_path`, encrypted with `keyprovider`.
+    ///
+    /// On [`Self::commit`] and [`Self::commit_with_keyprovider`] the client is written to its own file
+    /// instead of the shared [`Snapshot`] file, so that a compromised or corrupted file only affects a
+    /// single client. [`Self::load_client`] reads the client from its own file. As long as that file
+    /// does not exist, the client is loaded from the shared [`Snapshot`] and moved into its own file
+    /// on the next commit.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use iota_stronghold::{KeyProvider, SnapshotPath, Stronghold};
+    ///
+    /// let stronghold = Stronghold::default();
+    /// let tenant_key = KeyProvider::try_from(vec![1u8; 32]).unwrap();
+    /// stronghold
+    ///     .set_client_snapshot(b"tenant", SnapshotPath::named("tenant.stronghold"), tenant_key)
+    ///     .unwrap();
+    /// stronghold.create_client(b"tenant").unwrap();
+    ///
+    /// let shared_key = KeyProvider::try_from(vec![0u8; 32]).unwrap();
+    /// stronghold
+    ///     .commit_with_keyprovider(&SnapshotPath::named("shared.stronghold"), &shared_key)
+    ///     .unwrap();
+    /// ```
+    pub fn set_client_snapshot<P>(
+        &self,
+        client_path: P,
+        snapshot_path: SnapshotPath,
+        keyprovider: KeyProvider,
+    ) -> Result<(), ClientError>
+    where
+        P: AsRef<[u8]>,
+    {
+        let client_id = ClientId::load_from_path(client_path.as_ref(), client_path.as_ref());
+        self.client_snapshots
+            .write()?
+            .insert(client_id, (snapshot_path, keyprovider));
+        self.invalidate_warm_client(&client_id)?;
+        Ok(())
+    }
+
+    /// Removes the designated snapshot file of the [`Client`] at `client_path` and returns its path.
+    /// The file itself is not deleted.
+    ///
+    /// The client is written to the shared [`Snapshot`] file again on the next commit, if it is loaded.
+    pub fn remove_client_snapshot<P>(&self, client_path: P) -> Result<Option<SnapshotPath>, ClientError>
+    where
+        P: AsRef<[u8]>,
+    {
+        let client_id = ClientId::load_from_path(client_path.as_ref(), client_path.as_ref());
+        self.invalidate_warm_client(&client_id)?;
+        Ok(self
+            .client_snapshots
+            .write()?
+            .remove(&client_id)
+            .map(|(snapshot_path, _)| snapshot_path))
+    }
+
+    /// Decrypts the state of the [`Client`] with `client_id` from its designated snapshot file, if it
+    /// exists, or from the shared [`Snapshot`].
+    fn read_client_state(&self, snapshot: &Snapshot, client_id: ClientId) -> Result<ClientState, ClientError> {
+        let client_snapshots = self.client_snapshots.read()?;
+
+        let client_snapshot = match client_snapshots.get(&client_id) {
+            Some((snapshot_path, keyprovider)) if snapshot_path.exists() => {
+                let buffer = keyprovider
+                    .try_unlock()
+                    .map_err(|e| ClientError::Inner(format!("{:?}", e)))?;
+                let key = buffer
+                    .borrow()
+                    .deref()
+                    .try_into()
+                    .map_err(|_| ClientError::IllegalKeySize(32))?;
+                Some(Snapshot::read_from_snapshot_with_limit(
+                    snapshot_path,
+                    key,
+                    None,
+                    self.limits.max_snapshot_size(),
+                )?)
+            }
+            _ => None,
+        };
+        let snapshot = client_snapshot.as_ref().unwrap_or(snapshot);
+
+        if !snapshot.has_data(client_id) {
+            return Err(ClientError::ClientDataNotPresent);
+        }
+
+        snapshot
+            .get_state(client_id)
+            .map_err(|e| ClientError::Inner(e.to_string()))
+    }
+
+    /// Writes every [`Client`] with a designated snapshot file into that file, and removes it from the
+    /// shared `snapshot`. Clients that are not loaded are moved out of the shared `snapshot`, unless
+    /// their own file already exists.
+    ///
+    /// Returns the ids of the loaded clients that belong into the shared `snapshot`.
+    fn commit_client_snapshots(
+        &self,
+        snapshot: &mut Snapshot,
+        clients: &HashMap<ClientId, Client>,
+    ) -> Result<Vec<ClientId>, ClientError> {
+        let client_snapshots = self.client_snapshots.read()?;
+
+        for (client_id, (snapshot_path, keyprovider)) in client_snapshots.iter() {
+            let client_id = *client_id;
+            let mut client_snapshot = Snapshot::default();
+
+            if clients.contains_key(&client_id) {
+                write_with_clientid!(client_id, client_snapshot, clients);
+                self.invalidate_warm_client(&client_id)?;
+            } else if snapshot.has_data(client_id) && !snapshot_path.exists() {
+                let client_state = snapshot.get_state(client_id)?;
+                client_snapshot.add_data(client_id, client_state)?;
+            } else {
+                snapshot.purge_client(client_id)?;
+                continue;
+            }
+
+            if let Some(path) = snapshot_path.as_path().parent() {
+                std::fs::create_dir_all(path)
+                    .map_err(|_| ClientError::SnapshotFileMissing("Could not create snapshot file".to_string()))?;
+            }
+
+            // CRITICAL SECTION
+            let buffer = keyprovider
+                .try_unlock()
+                .map_err(|e| ClientError::Inner(format!("{:?}", e)))?;
+            let key = buffer
+                .borrow()
+                .deref()
+                .try_into()
+                .map_err(|_| ClientError::IllegalKeySize(32))?;
+
+            #[cfg(feature = "fault-injection")]
+            self.faults.snapshot_write()?;
+
+            client_snapshot.write_to_snapshot_with_limit(
+                snapshot_path,
+                UseKey::Key(key),
+                self.limits.max_snapshot_size(),
+            )?;
+            // END CRITICAL SECTION
+
+            snapshot.purge_client(client_id)?;
+        }
+
+        Ok(clients
+            .keys()
+            .filter(|client_id| !client_snapshots.contains_key(client_id))
+            .copied()
+            .collect())
+    }
+
     /// Unload the client from the clients currently managed by
     /// the [`Stronghold`] instance
     ///
@@ -210,13 +561,22 @@ impl Stronghold {
     }
 
     /// Purges a [`Client`] by wiping all state and remove it from
-    /// snapshot. This operation is destructive.
+    /// snapshot. If the client has a designated snapshot file, the file is deleted and
+    /// the designation is removed. This operation is destructive.
     ///
     /// # Example
     pub fn purge_client(&self, client: Client) -> Result<(), ClientError> {
         let mut snapshot = self.snapshot.write()?;
         let mut clients = self.clients.write()?;
         clients.remove(client.id());
+        self.warm_clients.write()?.remove(client.id());
+
+        // a designated snapshot file only contains the state of its client
+        if let Some((snapshot_path, _)) = self.client_snapshots.write()?.remove(client.id()) {
+            if snapshot_path.exists() {
+                std::fs::remove_file(snapshot_path.as_path()).map_err(SnapshotError::Io)?;
+            }
+        }
 
         snapshot
             .purge_client(*client.id())
@@ -229,7 +589,8 @@ impl Stronghold {
     /// # Example
     pub fn load_snapshot(&self, keyprovider: &KeyProvider, snapshot_path: &SnapshotPath) -> Result<(), ClientError> {
         let mut snapshot = self.snapshot.write()?;
-        load_snapshot!(snapshot, snapshot_path, keyprovider);
+        load_snapshot!(snapshot, snapshot_path, keyprovider, self.limits);
+        self.warm_clients.write()?.clear();
         Ok(())
     }
 
@@ -260,20 +621,114 @@ impl Stronghold {
         P: AsRef<[u8]>,
     {
         let client_id = ClientId::load_from_path(client_path.as_ref(), client_path.as_ref());
-        let client = Client {
-            id: client_id,
-            ..Default::default()
-        };
+        let client = self.new_client(client_id);
 
         // insert client as ref into Strongholds client ref
         let mut clients = self.clients.write()?;
+        self.invalidate_warm_client(&client_id)?;
         clients.insert(client_id, client.clone());
 
         Ok(client)
     }
 
+    /// Executes independent [`StrongholdProcedure`]s across multiple [`Client`]s concurrently.
+    ///
+    /// Each operation is a pair of a client path and the procedure to run on that client. Operations
+    /// targeting different clients run in parallel, while operations on the same client are executed
+    /// sequentially in the order they appear in `ops`. A failing operation does not affect operations
+    /// on other clients, nor subsequent operations on the same client.
+    ///
+    /// The results are returned in the same order as `ops`. An error is returned before executing any
+    /// operation, if one of the clients has not been loaded.
+    ///
+    /// # Example
+    /// ```
+    /// use iota_stronghold::{
+    ///     procedures::{GenerateKey, KeyType, StrongholdProcedure},
+    ///     Location, Stronghold,
+    /// };
+    ///
+    /// let stronghold = Stronghold::default();
+    /// stronghold.create_client(b"client-a").unwrap();
+    /// stronghold.create_client(b"client-b").unwrap();
+    ///
+    /// let generate = |record: &[u8]| {
+    ///     StrongholdProcedure::GenerateKey(GenerateKey {
+    ///         ty: KeyType::Ed25519,
+    ///         output: Location::const_generic(b"vault".to_vec(), record.to_vec()),
+    ///     })
+    /// };
+    ///
+    /// let results = stronghold
+    ///     .join_all(vec![
+    ///         (b"client-a".to_vec(), generate(b"key-1")),
+    ///         (b"client-b".to_vec(), generate(b"key-1")),
+    ///         (b"client-a".to_vec(), generate(b"key-2")),
+    ///     ])
+    ///     .unwrap();
+    /// assert!(results.iter().all(|result| result.is_ok()));
+    /// ```
+    pub fn join_all<P>(
+        &self,
+        ops: Vec<(P, StrongholdProcedure)>,
+    ) -> Result<Vec<Result<ProcedureOutput, ProcedureError>>, ClientError>
+    where
+        P: AsRef<[u8]>,
+    {
+        let count = ops.len();
+
+        // group the operations per client, keeping their original position
+        let mut queues: HashMap<ClientId, (Client, Vec<(usize, StrongholdProcedure)>)> = HashMap::new();
+        {
+            let clients = self.clients.read()?;
+            for (index, (client_path, procedure)) in ops.into_iter().enumerate() {
+                let client_id = ClientId::load_from_path(client_path.as_ref(), client_path.as_ref());
+                let client = clients.get(&client_id).ok_or(ClientError::ClientDataNotPresent)?;
+                queues
+                    .entry(client_id)
+                    .or_insert_with(|| (client.clone(), Vec::new()))
+                    .1
+                    .push((index, procedure));
+            }
+        }
+
+        let mut results: Vec<Option<Result<ProcedureOutput, ProcedureError>>> = (0..count).map(|_| None).collect();
+
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = queues
+                .into_values()
+                .map(|(client, queue)| {
+                    scope.spawn(move || {
+                        queue
+                            .into_iter()
+                            .map(|(index, procedure)| {
+                                let result = client
+                                    .execute_procedure_chained(vec![procedure])
+                                    .map(|mut output| output.pop().unwrap_or_else(|| ().into()));
+                                (index, result)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            for handle in handles {
+                let finished = handle
+                    .join()
+                    .map_err(|_| ClientError::Inner("Executing procedures on client panicked".to_string()))?;
+                for (index, result) in finished {
+                    results[index] = Some(result);
+                }
+            }
+            Ok::<_, ClientError>(())
+        })?;
+
+        Ok(results.into_iter().flatten().collect())
+    }
+
     /// Writes all client states into the [`Snapshot`] file using the `KeyProvider` to
-    /// encrypt the [`Snapshot`] file.
+    /// encrypt the [`Snapshot`] file. Clients with a designated snapshot file are written
+    /// into their own file instead, see [`Self::set_client_snapshot`].
     pub fn commit_with_keyprovider(
         &self,
         snapshot_path: &SnapshotPath,
@@ -293,10 +748,11 @@ impl Stronghold {
         let mut snapshot = self.snapshot.write()?;
         let clients = self.clients.read()?;
 
-        let ids: Vec<ClientId> = clients.iter().map(|(id, _)| *id).collect();
+        let ids = self.commit_client_snapshots(&mut snapshot, &clients)?;
 
         for client_id in ids {
             write_with_clientid!(client_id, snapshot, clients);
+            self.invalidate_warm_client(&client_id)?;
         }
 
         // CRITICAL SECTION
@@ -306,14 +762,22 @@ impl Stronghold {
         let buffer_ref = buffer.borrow();
         let key = buffer_ref.deref();
 
+        #[cfg(feature = "fault-injection")]
+        self.faults.snapshot_write()?;
+
         snapshot
-            .write_to_snapshot(snapshot_path, UseKey::Key(key.try_into().unwrap()))
-            .map_err(|e| ClientError::Inner(e.to_string()))?;
+            .write_to_snapshot_with_limit(
+                snapshot_path,
+                UseKey::Key(key.try_into().unwrap()),
+                self.limits.max_snapshot_size(),
+            )
+            .map_err(ClientError::from)?;
 
         Ok(())
     }
 
-    /// Writes all client states into the [`Snapshot`] file
+    /// Writes all client states into the [`Snapshot`] file. Clients with a designated snapshot
+    /// file are written into their own file instead, see [`Self::set_client_snapshot`].
     ///
     /// # Example
     pub fn commit(&self, snapshot_path: &SnapshotPath) -> Result<(), ClientError> {
@@ -330,10 +794,11 @@ impl Stronghold {
 
         let mut snapshot = self.snapshot.write()?;
         let clients = self.clients.read()?;
-        let ids: Vec<ClientId> = clients.iter().map(|(id, _)| *id).collect();
+        let ids = self.commit_client_snapshots(&mut snapshot, &clients)?;
 
         for client_id in ids {
             write_with_clientid!(client_id, snapshot, clients);
+            self.invalidate_warm_client(&client_id)?;
         }
 
         // CRITICAL SECTION
@@ -344,9 +809,16 @@ impl Stronghold {
             None => return Err(ClientError::SnapshotKeyLocationMissing),
         };
 
+        #[cfg(feature = "fault-injection")]
+        self.faults.snapshot_write()?;
+
         snapshot
-            .write_to_snapshot(snapshot_path, UseKey::Stored(key_location.clone()))
-            .map_err(|e| ClientError::Inner(e.to_string()))?;
+            .write_to_snapshot_with_limit(
+                snapshot_path,
+                UseKey::Stored(key_location.clone()),
+                self.limits.max_snapshot_size(),
+            )
+            .map_err(ClientError::from)?;
 
         Ok(())
     }
@@ -363,21 +835,27 @@ impl Stronghold {
 
         let client_id = ClientId::load_from_path(client_path.as_ref(), client_path.as_ref());
         write_with_clientid!(client_id, snapshot, clients);
+        self.invalidate_warm_client(&client_id)?;
         Ok(())
     }
 
     /// Calling this function clears the runtime state of all [`Client`]s and the in-memory
     /// [`Snapshot`] state. This does not affect the persisted [`Client`] state inside a
-    /// snapshot file. Use [`Self::load_client_from_snapshot`] to reload any [`Client`] and
+    /// snapshot file. Designated snapshot files of clients are forgotten, but not deleted.
+    /// Use [`Self::load_client_from_snapshot`] to reload any [`Client`] and
     /// [`Snapshot`] state
     pub fn clear(&self) -> Result<(), ClientError> {
         self.snapshot.write()?.clear()?;
+        self.client_snapshots.write()?.clear();
         let mut clients = self.clients.write()?;
         self.store.clear()?;
         self.key_location.write()?.take();
         for (_, client) in clients.drain() {
             client.clear()?;
         }
+        for (_, client) in self.warm_clients.write()?.drain() {
+            client.clear()?;
+        }
         Ok(())
     }
 }
@@ -1,11 +1,46 @@
 // Copyright 2020-2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{derive_vault_id, procedures::Runner, Client, ClientError, Location};
+use crate::{
+    derive_vault_id,
+    procedures::{RecordKind, Runner},
+    Client, ClientError, Location,
+};
+use crypto::keys::slip10::Chain;
 use engine::vault::VaultId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 pub const DEFAULT_RANDOM_HINT_SIZE: usize = 24;
 
+/// Statistics of a vault, as returned by [`Client::vault_stats`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultStats {
+    /// The number of records in the vault, including revoked records that have not been
+    /// garbage collected yet.
+    pub records: usize,
+
+    /// A token derived from the ids and content ids of all records in the vault. The token changes
+    /// whenever a record is written, revoked or removed, and is required by [`Client::delete_vault`].
+    pub confirmation: [u8; 32],
+}
+
+/// A record that was derived with a SLIP10 procedure, as returned by [`Client::derivation_tree`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DerivedRecord {
+    /// The location of the derived record.
+    pub location: Location,
+
+    /// The location of the seed or parent key that the record was derived from.
+    pub source: Location,
+
+    /// The derivation path, relative to the `source`.
+    pub chain: Chain,
+}
+
+/// The [`DerivedRecord`]s of a [`Client`] per vault.
+pub(crate) type DerivationTree = HashMap<VaultId, Vec<DerivedRecord>>;
+
 pub struct ClientVault {
     /// An atomic but inner mutable back reference to the [`Client`]
     pub(crate) client: Client,
@@ -19,11 +54,26 @@ pub struct ClientVault {
 /// to store secrets and execute [`crate::procedures::Procedure`]s on them. Data stored inside a [`ClientVault`] can
 /// never be directly access, nor will its contents ever be exposed.
 impl ClientVault {
-    /// Writes a secret into the vault
+    /// Writes a secret into the vault. Fails with [`ClientError::SizeLimitExceeded`], if the
+    /// payload is larger than the configured maximum record size.
     ///
     /// # Example
     pub fn write_secret(&self, location: Location, payload: Vec<u8>) -> Result<(), ClientError> {
-        self.client.write_to_vault(&location, payload)?;
+        self.write_secret_with_kind(location, payload, RecordKind::Raw)
+    }
+
+    /// Writes a secret of the given [`RecordKind`] into the vault. Procedures that use the secret
+    /// as input check its kind, whereas secrets of kind [`RecordKind::Raw`] are accepted by all
+    /// procedures.
+    pub fn write_secret_with_kind(
+        &self,
+        location: Location,
+        payload: Vec<u8>,
+        kind: RecordKind,
+    ) -> Result<(), ClientError> {
+        self.client.limits.check_record_size(payload.len())?;
+        self.client.write_record(&location, payload, kind)?;
+        self.client.track_derivations(&[location], Vec::new());
         Ok(())
     }
 
@@ -30,6 +30,7 @@ mod stronghold_test_std {
         let write_procedure = WriteVault {
             data: expected.clone(),
             location: location.clone(),
+            kind: None,
         };
 
         let checking_procedure = CompareSecret { location, expected };
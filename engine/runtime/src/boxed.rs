@@ -1,7 +1,7 @@
 // Copyright 2020-2021 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::types::*;
+use crate::{types::*, MemoryError};
 use zeroize::Zeroize;
 
 use core::{
@@ -315,6 +315,31 @@ fn mprotect<T>(ptr: *mut T, prot: Prot) {
     }
 }
 
+/// Checks that the memory protections used by [`Boxed`] are available: libsodium can be initialized,
+/// and a guarded allocation can be locked into memory and have its access rights changed.
+pub(crate) fn check_protection() -> Result<(), MemoryError> {
+    if unsafe { sodium_init() == -1 } {
+        return Err(MemoryError::Operation("failed to initialize libsodium".into()));
+    }
+
+    let ptr = unsafe { sodium_allocarray(1, mem::size_of::<u8>()) };
+    if ptr.is_null() {
+        return Err(MemoryError::Allocation("guarded allocation failed".into()));
+    }
+
+    let res = if unsafe { sodium_mlock(ptr, 1) } != 0 {
+        Err(MemoryError::LockNotAvailable)
+    } else if unsafe { sodium_mprotect_noaccess(ptr) } != 0 || unsafe { sodium_mprotect_readwrite(ptr) } != 0 {
+        Err(MemoryError::Operation("failed to change memory protection".into()))
+    } else {
+        Ok(())
+    };
+
+    // restores the access rights and unlocks the memory before releasing it
+    unsafe { sodium_free(ptr) };
+    res
+}
+
 pub(crate) unsafe fn free<T>(ptr: *mut T) {
     sodium_free(ptr as *mut _)
 }
@@ -347,6 +372,11 @@ mod test {
         boxed.lock();
     }
 
+    #[test]
+    fn test_check_protection() {
+        assert!(check_protection().is_ok());
+    }
+
     #[test]
     fn test_init_with_garbage() {
         let boxed = Boxed::<u8>::new(4, |_| {});
@@ -50,5 +50,11 @@ pub enum MemoryError {
     IllegalZeroizedUsage,
 }
 
+/// Verifies that the memory protections of the guarded memory types are available on this system,
+/// i.e. that memory can be locked into RAM and its access rights can be changed.
+pub fn check_memory_protection() -> Result<(), MemoryError> {
+    boxed::check_protection()
+}
+
 /// A simple trait to force the types to call `zeroize()` when dropping
 pub trait ZeroizeOnDrop {}
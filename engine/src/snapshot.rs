@@ -27,5 +27,5 @@ mod compression;
 pub mod files;
 
 mod logic;
-pub use compression::{compress, decompress, Lz4DecodeError};
+pub use compression::{compress, decompress, decompress_with_limit, Lz4DecodeError};
 pub use logic::*;
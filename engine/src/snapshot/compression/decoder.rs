@@ -9,10 +9,17 @@ pub struct Lz4DecodeError(String);
 
 /// Public function to decompress some data into an output.
 pub fn decompress_into(input: &[u8], output: &mut Vec<u8>) -> Result<(), Lz4DecodeError> {
+    decompress_into_with_limit(input, output, usize::MAX)
+}
+
+/// Decompresses some data into an output, but stops as soon as the output is longer than `limit`.
+/// Callers detect this by checking the length of the output.
+pub fn decompress_into_with_limit(input: &[u8], output: &mut Vec<u8>, limit: usize) -> Result<(), Lz4DecodeError> {
     Lz4Decoder {
         input,
         output,
         token: 0,
+        limit,
     }
     .complete()?;
 
@@ -28,11 +35,22 @@ pub fn decompress(input: &[u8]) -> Result<Vec<u8>, Lz4DecodeError> {
     Ok(vec)
 }
 
+/// Decompress data using an LZ4 Algorithm. Decompression stops as soon as the output is longer
+/// than `limit`, so that a small input can not expand into an arbitrarily large output.
+pub fn decompress_with_limit(input: &[u8], limit: usize) -> Result<Vec<u8>, Lz4DecodeError> {
+    let mut vec = Vec::with_capacity(4096.min(limit));
+
+    decompress_into_with_limit(input, &mut vec, limit)?;
+
+    Ok(vec)
+}
+
 /// Lz4Decoder implementation.
 struct Lz4Decoder<'a> {
     input: &'a [u8],
     output: &'a mut Vec<u8>,
     token: u8,
+    limit: usize,
 }
 
 impl<'a> Lz4Decoder<'a> {
@@ -57,6 +75,11 @@ impl<'a> Lz4Decoder<'a> {
         output.extend_from_slice(&buf[..buf.len()]);
     }
 
+    /// Number of bytes that can be written before the output exceeds the limit by one byte.
+    fn capacity(&self) -> usize {
+        self.limit.saturating_add(1).saturating_sub(self.output.len())
+    }
+
     fn duplicate(&mut self, start: usize, length: usize) {
         for i in start..start + length {
             let b = self.output[i];
@@ -92,7 +115,9 @@ impl<'a> Lz4Decoder<'a> {
             literal += self.read_int()?;
         }
 
-        Self::output(self.output, Self::take_internal(&mut self.input, literal)?);
+        let capacity = self.capacity();
+        let literal = Self::take_internal(&mut self.input, literal)?;
+        Self::output(self.output, &literal[..literal.len().min(capacity)]);
 
         Ok(())
     }
@@ -109,7 +134,7 @@ impl<'a> Lz4Decoder<'a> {
         let start = self.output.len().wrapping_sub(offset as usize);
 
         if start < self.output.len() {
-            self.duplicate(start, length);
+            self.duplicate(start, length.min(self.capacity()));
 
             Ok(())
         } else {
@@ -119,7 +144,7 @@ impl<'a> Lz4Decoder<'a> {
 
     #[inline]
     fn complete(&mut self) -> Result<(), Lz4DecodeError> {
-        while !self.input.is_empty() {
+        while !self.input.is_empty() && self.output.len() <= self.limit {
             self.token = self.take(1)?[0];
 
             self.read_literal()?;
@@ -15,7 +15,7 @@ use crypto::{
 };
 use thiserror::Error as DeriveError;
 
-use crate::snapshot::{compress, decompress};
+use crate::snapshot::{compress, decompress, decompress_with_limit};
 
 /// Magic bytes (bytes 0-4 in a snapshot file) aka PARTI
 pub const MAGIC: [u8; 5] = [0x50, 0x41, 0x52, 0x54, 0x49];
@@ -47,6 +47,9 @@ pub enum ReadError {
 
     #[error("unsupported version: expected `{expected:?}`, found `{found:?}`")]
     UnsupportedVersion { expected: [u8; 2], found: [u8; 2] },
+
+    #[error("snapshot content of at least {size} bytes exceeds the maximum of {max} bytes")]
+    SizeLimitExceeded { size: usize, max: usize },
 }
 
 #[derive(Debug, DeriveError)]
@@ -205,9 +208,48 @@ pub fn read_from(path: &Path, key: &Key, associated_data: &[u8]) -> Result<Vec<u
     decompress(&pt).map_err(|e| ReadError::CorruptedContent(format!("Decompression failed: {}", e)))
 }
 
+/// Like [`read_from`], but fails with [`ReadError::SizeLimitExceeded`], if the decompressed content
+/// is larger than `max_size`. Files that are too large to hold at most `max_size` bytes of content
+/// are rejected before they are decrypted, and decompression stops as soon as the limit is exceeded.
+pub fn read_from_with_limit(
+    path: &Path,
+    key: &Key,
+    associated_data: &[u8],
+    max_size: usize,
+) -> Result<Vec<u8>, ReadError> {
+    let mut f: File = OpenOptions::new().read(true).open(path)?;
+    check_min_file_len(&mut f)?;
+
+    // the compressed content can be slightly larger than the content itself: the encoder writes
+    // one length byte per 255 bytes of literals, and a token per block.
+    let compressed = usize::try_from(f.metadata()?.len() - MIN_FILE_LEN as u64).unwrap_or(usize::MAX);
+    if compressed > max_size.saturating_add(max_size / 255).saturating_add(16) {
+        return Err(ReadError::SizeLimitExceeded {
+            size: compressed,
+            max: max_size,
+        });
+    }
+
+    // check the header for structure.
+    check_header(&mut f)?;
+    let pt = read(&mut f, key, associated_data)?;
+
+    let plain = decompress_with_limit(&pt, max_size)
+        .map_err(|e| ReadError::CorruptedContent(format!("Decompression failed: {}", e)))?;
+    if plain.len() > max_size {
+        return Err(ReadError::SizeLimitExceeded {
+            size: plain.len(),
+            max: max_size,
+        });
+    }
+    Ok(plain)
+}
+
+/// Length of a snapshot file with empty content.
+const MIN_FILE_LEN: usize = MAGIC.len() + VERSION.len() + x25519::PUBLIC_KEY_LENGTH + XChaCha20Poly1305::TAG_LENGTH;
+
 fn check_min_file_len(input: &mut File) -> Result<(), ReadError> {
-    let min = MAGIC.len() + VERSION.len() + x25519::PUBLIC_KEY_LENGTH + XChaCha20Poly1305::TAG_LENGTH;
-    if input.metadata()?.len() >= min as u64 {
+    if input.metadata()?.len() >= MIN_FILE_LEN as u64 {
         Ok(())
     } else {
         Err(ReadError::InvalidFile)
@@ -297,6 +339,38 @@ mod test {
         assert_eq!(bs0, bs1);
     }
 
+    #[test]
+    fn test_snapshot_size_limit() {
+        let f = tempfile::tempdir().unwrap();
+        let mut pb = f.into_path();
+        pb.push("snapshot");
+
+        let key: Key = random_key();
+        let ad = random_bytestring();
+
+        // random data does not compress
+        let bs0 = random::fixed_bytestring(4096);
+        write_to(&bs0, &pb, &key, &ad).unwrap();
+        assert_eq!(read_from_with_limit(&pb, &key, &ad, 4096).unwrap(), bs0);
+        assert!(matches!(
+            read_from_with_limit(&pb, &key, &ad, 4095),
+            Err(ReadError::SizeLimitExceeded { max: 4095, .. })
+        ));
+
+        // files that are too large are rejected before they are decrypted
+        assert!(matches!(
+            read_from_with_limit(&pb, &random_key(), &ad, 1024),
+            Err(ReadError::SizeLimitExceeded { max: 1024, .. })
+        ));
+
+        // decompression stops once the limit is exceeded
+        write_to(&[0u8; 1 << 16], &pb, &key, &ad).unwrap();
+        assert!(matches!(
+            read_from_with_limit(&pb, &key, &ad, 1024),
+            Err(ReadError::SizeLimitExceeded { size: 1025, max: 1024 })
+        ));
+    }
+
     #[test]
     #[should_panic]
     fn test_currupted_snapshot() {
@@ -28,4 +28,15 @@ impl<T> Value<T> {
     pub fn has_expired(&self, time_now: SystemTime) -> bool {
         self.expiration.map_or(false, |time| time_now >= time)
     }
+
+    /// Gets the remaining lifetime of the [`Value`], or [`None`] if it never expires.
+    pub fn remaining_lifetime(&self, time_now: SystemTime) -> Option<Duration> {
+        self.expiration
+            .map(|time| time.duration_since(time_now).unwrap_or_default())
+    }
+
+    /// Replaces the expiration of the [`Value`], starting from `time_now`.
+    pub fn set_lifetime(&mut self, lifetime: Option<Duration>, time_now: SystemTime) {
+        self.expiration = lifetime.map(|d| time_now + d);
+    }
 }
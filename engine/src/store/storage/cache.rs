@@ -198,6 +198,63 @@ impl<K: Hash + Eq + Clone, V: Clone + Debug> Cache<K, V> {
             .map(|value| value.val)
     }
 
+    /// Gets the remaining lifetime of the value associated with the specified key. Returns [`None`], if the key
+    /// could not be found in the [`Cache`], and `Some(None)`, if the value never expires.
+    ///
+    /// # Example
+    /// ```
+    /// use engine::store::Cache;
+    /// use std::time::Duration;
+    ///
+    /// let mut cache = Cache::new();
+    ///
+    /// cache.insert("key", "value", Some(Duration::from_secs(60)));
+    /// cache.insert("forever", "value", None);
+    ///
+    /// assert!(cache.lifetime(&"key").flatten().unwrap() <= Duration::from_secs(60));
+    /// assert_eq!(cache.lifetime(&"forever"), Some(None));
+    /// assert_eq!(cache.lifetime(&"missing"), None);
+    /// ```
+    pub fn lifetime(&self, key: &K) -> Option<Option<Duration>> {
+        let now = SystemTime::now();
+
+        self.table
+            .get(key)
+            .filter(|value| !value.has_expired(now))
+            .map(|value| value.remaining_lifetime(now))
+    }
+
+    /// Replaces the lifetime of the value associated with the specified key, without rewriting the value. The new
+    /// `lifetime` starts now, and [`None`] removes the expiration. Returns `false`, if the key could not be found
+    /// in the [`Cache`].
+    ///
+    /// # Example
+    /// ```
+    /// use engine::store::Cache;
+    /// use std::time::Duration;
+    ///
+    /// let mut cache = Cache::new();
+    ///
+    /// cache.insert("key", "value", Some(Duration::from_secs(60)));
+    ///
+    /// assert!(cache.set_lifetime(&"key", None));
+    /// assert_eq!(cache.lifetime(&"key"), Some(None));
+    /// assert!(!cache.set_lifetime(&"missing", None));
+    /// ```
+    pub fn set_lifetime(&mut self, key: &K, lifetime: Option<Duration>) -> bool {
+        let now = SystemTime::now();
+
+        self.try_remove_expired_items(now);
+
+        match self.table.get_mut(key).filter(|value| !value.has_expired(now)) {
+            Some(value) => {
+                value.set_lifetime(lifetime, now);
+                true
+            }
+            None => false,
+        }
+    }
+
     // Check if the [`Cache<K, V>`] contains a specific key.
     pub fn contains_key(&self, key: &K) -> bool {
         let now = SystemTime::now();
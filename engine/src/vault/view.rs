@@ -137,6 +137,16 @@ impl<P: BoxProvider> DbView<P> {
         Ok(blob_id)
     }
 
+    /// Get the [`RecordHint`] that was stored with the specified [`Record`].
+    pub fn get_hint<E>(&self, key: &Key<P>, vid: VaultId, rid: RecordId) -> Result<RecordHint, VaultError<P::Error, E>>
+    where
+        E: Debug,
+    {
+        let vault = self.vaults.get(&vid).ok_or(VaultError::VaultNotFound(vid))?;
+        let hint = vault.get_hint(key, rid.0).map_err(VaultError::Record)?;
+        Ok(hint)
+    }
+
     /// Get the buffers for the given records, using the provided key for access.
     fn get_buffers<E, const N: usize>(
         &self,
@@ -212,6 +222,50 @@ impl<P: BoxProvider> DbView<P> {
             .map_err(VaultError::Record)
     }
 
+    /// Access the decrypted [`Buffer`]s of the specified [`Record`]s and place each of the
+    /// returned values into the corresponding target [`Record`]. `f` must return one value per target.
+    ///
+    /// The targets are written atomically: if any write fails, the records written so far are
+    /// removed again, or restored to their previous content if they already existed.
+    pub fn exec_procedure_multi<E, F, const N: usize>(
+        &mut self,
+        sources: [(Key<P>, VaultId, RecordId); N],
+        targets: Vec<(Key<P>, VaultId, RecordId, RecordHint)>,
+        f: F,
+    ) -> Result<(), VaultError<P::Error, E>>
+    where
+        F: FnOnce([Buffer<u8>; N]) -> Result<Vec<Vec<u8>>, E>,
+        E: Debug,
+    {
+        let buffers: [Buffer<u8>; N] = self.get_buffers(sources)?;
+
+        let data: Vec<Vec<u8>> = f(buffers).map_err(VaultError::Procedure)?;
+
+        // previous state of each written record, and whether its vault existed before
+        let mut written: Vec<(VaultId, RecordId, Option<Record>, bool)> = Vec::with_capacity(targets.len());
+
+        for ((key, vid, rid, hint), data) in targets.into_iter().zip(data) {
+            let vault = self.vaults.get(&vid);
+            let previous = vault.and_then(|v| v.entries.get(&rid.0).cloned());
+            written.push((vid, rid, previous, vault.is_some()));
+
+            if let Err(e) = self.write(&key, vid, rid, &data, hint) {
+                for (vid, rid, previous, vault_existed) in written.into_iter().rev() {
+                    if !vault_existed {
+                        self.vaults.remove(&vid);
+                    } else if let Some(vault) = self.vaults.get_mut(&vid) {
+                        match previous {
+                            Some(record) => vault.entries.insert(rid.0, record),
+                            None => vault.entries.remove(&rid.0),
+                        };
+                    }
+                }
+                return Err(VaultError::Record(e));
+            }
+        }
+        Ok(())
+    }
+
     /// Add a revocation transaction to the [`Record`]
     pub fn revoke_record(&mut self, key: &Key<P>, vid: VaultId, rid: RecordId) -> Result<(), RecordError<P::Error>> {
         if let Some(vault) = self.vaults.get_mut(&vid) {
@@ -429,6 +483,16 @@ impl<P: BoxProvider> Vault<P> {
             .and_then(|r| r.get_blob_id(key, id))
     }
 
+    /// Gets the [`RecordHint`] of the record with the given [`ChainId`].
+    pub fn get_hint(&self, key: &Key<P>, id: ChainId) -> Result<RecordHint, RecordError<P::Error>> {
+        self.check_key(key)?;
+        self.entries
+            .get(&id)
+            .ok_or(RecordError::RecordNotFound(id))
+            .and_then(|r| r.get_hint_and_id(key))
+            .map(|(_, hint)| hint)
+    }
+
     fn check_key(&self, key: &Key<P>) -> Result<(), RecordError<P::Error>> {
         if key == &self.key {
             Ok(())
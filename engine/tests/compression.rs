@@ -5,7 +5,7 @@ use std::str;
 
 use proptest::proptest;
 
-use engine::snapshot::{compress, decompress};
+use engine::snapshot::{compress, decompress, decompress_with_limit};
 
 const LOREM_STR: &str = include_str!("lorem.txt");
 const ZAPPA_STR: &str = include_str!("zappa.txt");
@@ -34,6 +34,18 @@ fn test_compression_zappa() {
     compression(ZAPPA_STR);
 }
 
+#[test]
+fn test_decompression_limit() {
+    let compressed = compress(LOREM_STR.as_bytes());
+    let limit = LOREM_STR.len() / 2;
+    let decompressed = decompress_with_limit(&compressed, limit).unwrap();
+    assert_eq!(decompressed, LOREM_STR.as_bytes()[..limit + 1]);
+    assert_eq!(
+        decompress_with_limit(&compressed, LOREM_STR.len()).unwrap(),
+        LOREM_STR.as_bytes()
+    );
+}
+
 proptest! {
     #[test]
     fn prop_check_encode_decode(s in "[a-zA-Z0-9._!~$&'()*+;,=/?:@-]+[a-zA-Z0-9._!~$&'()*+;,=/?:@-]+") {
@@ -74,3 +74,29 @@ fn test_scanner() {
 
     assert!(scanner.is_some())
 }
+
+#[test]
+fn test_lifetime() {
+    let mut cache = Cache::new();
+    let key: &'static str = "key";
+
+    cache.insert(key, 1, Some(Duration::from_secs(60)));
+    let lifetime = cache.lifetime(&key).flatten().unwrap();
+    assert!(lifetime <= Duration::from_secs(60));
+
+    assert!(cache.set_lifetime(&key, None));
+    assert_eq!(cache.lifetime(&key), Some(None));
+    assert_eq!(cache.get(&key), Some(&1));
+}
+
+#[test]
+fn test_set_lifetime_expired() {
+    let mut cache = Cache::new();
+    let key: &'static str = "key";
+
+    cache.insert(key, 1, Some(Duration::default()));
+
+    assert_eq!(cache.lifetime(&key), None);
+    assert!(!cache.set_lifetime(&key, None));
+    assert_eq!(cache.get(&key), None);
+}
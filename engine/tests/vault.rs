@@ -38,6 +38,13 @@ fn test_vaults() {
 
     assert_eq!(list0.len(), 2);
 
+    // read the hint of a single record
+    assert_eq!(
+        view.get_hint::<Infallible>(&key0, vid0, rid0).unwrap(),
+        RecordHint::new(b"hint").unwrap()
+    );
+    assert!(view.get_hint::<Infallible>(&key1, vid0, rid0).is_err());
+
     // read from vault0 and record0
     view.get_guard::<Infallible, _>(&key0, vid0, rid0, |g| {
         assert_eq!(b"test0", &(*g.borrow()));
@@ -140,3 +147,88 @@ fn test_vaults() {
     })
     .unwrap();
 }
+
+#[test]
+fn test_exec_procedure_multi() {
+    let mut view: DbView<Provider> = DbView::new();
+
+    let key0 = Key::random();
+    let vid0 = VaultId::random::<Provider>().unwrap();
+    let rid0 = RecordId::random::<Provider>().unwrap();
+
+    let key1 = Key::random();
+    let vid1 = VaultId::random::<Provider>().unwrap();
+    let rid1 = RecordId::random::<Provider>().unwrap();
+    let rid2 = RecordId::random::<Provider>().unwrap();
+
+    view.write(&key0, vid0, rid0, b"test", RecordHint::new(b"hint").unwrap())
+        .unwrap();
+
+    // execute a procedure and put the results into two new records
+    view.exec_procedure_multi::<Infallible, _, 1>(
+        [(key0.clone(), vid0, rid0)],
+        vec![
+            (key1.clone(), vid1, rid1, RecordHint::new(b"first").unwrap()),
+            (key1.clone(), vid1, rid2, RecordHint::new(b"second").unwrap()),
+        ],
+        |guards| {
+            let data = guards[0].borrow();
+            Ok(vec![data.to_vec(), data.iter().rev().copied().collect()])
+        },
+    )
+    .unwrap();
+
+    view.get_guard::<Infallible, _>(&key1, vid1, rid1, |g| {
+        assert_eq!(b"test", &(*g.borrow()));
+        Ok(())
+    })
+    .unwrap();
+    view.get_guard::<Infallible, _>(&key1, vid1, rid2, |g| {
+        assert_eq!(b"tset", &(*g.borrow()));
+        Ok(())
+    })
+    .unwrap();
+
+    // the second write uses the wrong key, so the first one is rolled back
+    let rid3 = RecordId::random::<Provider>().unwrap();
+    let rid4 = RecordId::random::<Provider>().unwrap();
+    let res = view.exec_procedure_multi::<Infallible, _, 1>(
+        [(key0.clone(), vid0, rid0)],
+        vec![
+            (key1.clone(), vid1, rid3, RecordHint::new(b"third").unwrap()),
+            (key0.clone(), vid1, rid4, RecordHint::new(b"fourth").unwrap()),
+        ],
+        |guards| {
+            let data = guards[0].borrow();
+            Ok(vec![data.to_vec(), data.to_vec()])
+        },
+    );
+    assert!(res.is_err());
+    assert!(!view.contains_record(vid1, rid3));
+    assert!(!view.contains_record(vid1, rid4));
+    assert!(view.contains_record(vid1, rid1));
+    assert!(view.contains_record(vid1, rid2));
+
+    // overwritten records are restored and new vaults are removed if a later write fails
+    let key2 = Key::random();
+    let vid2 = VaultId::random::<Provider>().unwrap();
+    let rid5 = RecordId::random::<Provider>().unwrap();
+    let rid6 = RecordId::random::<Provider>().unwrap();
+    let res = view.exec_procedure_multi::<Infallible, _, 1>(
+        [(key0.clone(), vid0, rid0)],
+        vec![
+            (key1.clone(), vid1, rid1, RecordHint::new(b"overwritten").unwrap()),
+            (key2, vid2, rid5, RecordHint::new(b"new vault").unwrap()),
+            (key0, vid1, rid6, RecordHint::new(b"wrong key").unwrap()),
+        ],
+        |_| Ok(vec![b"new".to_vec(), b"new".to_vec(), b"new".to_vec()]),
+    );
+    assert!(res.is_err());
+    view.get_guard::<Infallible, _>(&key1, vid1, rid1, |g| {
+        assert_eq!(b"test", &(*g.borrow()));
+        Ok(())
+    })
+    .unwrap();
+    assert!(!view.contains_vault(&vid2));
+    assert!(!view.contains_record(vid1, rid6));
+}